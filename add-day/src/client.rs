@@ -0,0 +1,307 @@
+// All requests to the advent of code website. The base URL can be changed with the
+// AOC_BASE_URL env variable or in the .env file, which the tests use to talk to a mock server.
+// Following the automation guidelines of the website, every request has a User-Agent that
+// identifies this repository (AOC_USER_AGENT) and inputs are cached on disk, so each input
+// is only downloaded once.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use aoc_utils::ledger::Part;
+
+use crate::session;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "github.com/daniel0611/AdventOfCode2023 add-day/",
+    env!("CARGO_PKG_VERSION")
+);
+pub const DEFAULT_CACHE_DIR: &str = ".aoc-cache";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FetchError {
+    NoSession,
+    /// The website rejected the session cookie, which usually means it expired.
+    ExpiredSession,
+    /// The puzzle doesn't exist yet, because it isn't unlocked.
+    NotUnlocked,
+    ServerError(u16),
+    UnexpectedStatus(u16),
+    /// The request didn't get a response, e.g. because there is no connection.
+    Transport(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NoSession => write!(f, "No AOC_SESSION set"),
+            FetchError::ExpiredSession => write!(
+                f,
+                "The session was rejected, it probably expired. Log in again and update AOC_SESSION"
+            ),
+            FetchError::NotUnlocked => write!(f, "The puzzle is not unlocked yet"),
+            FetchError::ServerError(status) => {
+                write!(
+                    f,
+                    "The server failed with status {}, try again later",
+                    status
+                )
+            }
+            FetchError::UnexpectedStatus(status) => write!(f, "Unexpected status {}", status),
+            FetchError::Transport(e) => write!(f, "The request failed: {}", e),
+        }
+    }
+}
+
+pub struct Client {
+    base_url: String,
+    session: Option<String>,
+    user_agent: String,
+    /// Where downloaded inputs are kept, caching is off without it.
+    cache_dir: Option<PathBuf>,
+}
+
+impl Client {
+    /// A client without cache that uses the default User-Agent.
+    pub fn new(base_url: &str, session: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            cache_dir: None,
+        }
+    }
+
+    /// Uses AOC_BASE_URL, AOC_SESSION, AOC_USER_AGENT and AOC_CACHE_DIR from the environment.
+    /// Without AOC_SESSION, the session stored with `add-day session set` is used.
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: &str| std::env::var(name).unwrap_or(default.to_string());
+        let session = std::env::var("AOC_SESSION")
+            .ok()
+            .filter(|session| !session.is_empty())
+            .or_else(session::load_stored);
+        let mut client = Self::new(&var("AOC_BASE_URL", DEFAULT_BASE_URL), session);
+        client.user_agent = var("AOC_USER_AGENT", DEFAULT_USER_AGENT);
+        client.with_cache_dir(var("AOC_CACHE_DIR", DEFAULT_CACHE_DIR))
+    }
+
+    pub fn with_cache_dir<P: Into<PathBuf>>(mut self, cache_dir: P) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    pub fn with_session(mut self, session: String) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn has_session(&self) -> bool {
+        self.session.is_some()
+    }
+
+    pub fn has_session_token(&self, token: &str) -> bool {
+        self.session.as_deref() == Some(token)
+    }
+
+    fn session_cookie(&self) -> Result<String, FetchError> {
+        let session = self.session.as_ref().ok_or(FetchError::NoSession)?;
+        Ok(format!("session={}", session))
+    }
+
+    fn day_url(&self, year: u16, day: u8) -> String {
+        format!("{}/{}/day/{}", self.base_url, year, day)
+    }
+
+    fn cache_path(&self, year: u16, day: u8) -> Option<PathBuf> {
+        let cache_dir = self.cache_dir.as_ref()?;
+        Some(
+            cache_dir
+                .join(year.to_string())
+                .join(format!("input_day{:02}.txt", day)),
+        )
+    }
+
+    /// Returns the input from the cache or downloads it and adds it to the cache.
+    pub fn fetch_input(&self, year: u16, day: u8) -> Result<String, FetchError> {
+        let cache_path = self.cache_path(year, day);
+        if let Some(input) = cache_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .filter(|input| !input.is_empty())
+        {
+            println!("Using the cached input of {} day {}", year, day);
+            return Ok(input);
+        }
+
+        let input = self.fetch(&format!("{}/input", self.day_url(year, day)), "input")?;
+        if let Some(path) = cache_path {
+            let cached =
+                fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, &input));
+            if let Err(e) = cached {
+                println!("Could not cache the input in {}: {}", path.display(), e);
+            }
+        }
+        Ok(input)
+    }
+
+    /// Fetches the page of the puzzle, which includes part two once part one is solved.
+    pub fn fetch_puzzle(&self, year: u16, day: u8) -> Result<String, FetchError> {
+        self.fetch(&self.day_url(year, day), "puzzle description")
+    }
+
+    /// Fetches the overview page of the year, which shows the user if the session is valid.
+    pub fn fetch_event(&self, year: u16) -> Result<String, FetchError> {
+        self.fetch(&format!("{}/{}", self.base_url, year), "event page")
+    }
+
+    /// Posts the answer for the part and returns the response page.
+    pub fn post_answer(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+    ) -> Result<String, FetchError> {
+        let cookie = self.session_cookie()?;
+        let url = format!("{}/answer", self.day_url(year, day));
+        println!("Submitting {} for part {} to {}...", answer, part, url);
+        let response = ureq::post(&url)
+            .set("Cookie", &cookie)
+            .set("User-Agent", &self.user_agent)
+            .send_form(&[("level", &part.level().to_string()), ("answer", answer)]);
+        read_response(response)
+    }
+
+    fn fetch(&self, url: &str, what: &str) -> Result<String, FetchError> {
+        let cookie = self.session_cookie()?;
+        println!("Fetching {} from {}...", what, url);
+        let response = ureq::get(url)
+            .set("Cookie", &cookie)
+            .set("User-Agent", &self.user_agent)
+            .call();
+        let body = read_response(response)?;
+        println!("Fetched {} successfully!", what);
+        Ok(body)
+    }
+}
+
+/// Maps the status codes the website uses to errors. ureq already turns every status
+/// of 400 and above into an error.
+fn read_response(response: Result<ureq::Response, ureq::Error>) -> Result<String, FetchError> {
+    match response {
+        Ok(response) => response
+            .into_string()
+            .map_err(|e| FetchError::Transport(e.to_string())),
+        Err(ureq::Error::Status(400 | 401 | 403, _)) => Err(FetchError::ExpiredSession),
+        Err(ureq::Error::Status(404, _)) => Err(FetchError::NotUnlocked),
+        Err(ureq::Error::Status(status, _)) if status >= 500 => {
+            Err(FetchError::ServerError(status))
+        }
+        Err(ureq::Error::Status(status, _)) => Err(FetchError::UnexpectedStatus(status)),
+        Err(ureq::Error::Transport(e)) => Err(FetchError::Transport(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockServer, LOCKED_FROM, SERVER_ERROR_YEAR, SESSION};
+
+    #[test]
+    fn test_fetch() {
+        let server = MockServer::start();
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+
+        assert_eq!(
+            client.fetch_input(2023, 4),
+            Ok("input of 2023 day 4\n".to_string())
+        );
+        let page = client.fetch_puzzle(2023, 4).unwrap();
+        assert!(page.contains("--- Part Two ---"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/2023/day/4/input");
+        assert_eq!(requests[1].path, "/2023/day/4");
+        assert_eq!(requests[1].cookie.as_deref(), Some("session=test-session"));
+        let user_agent = requests[1].user_agent.as_deref().unwrap();
+        assert_eq!(user_agent, DEFAULT_USER_AGENT);
+        assert!(user_agent.starts_with("github.com/daniel0611/AdventOfCode2023 add-day/"));
+    }
+
+    #[test]
+    fn test_input_cache() {
+        let server = MockServer::start();
+        let cache_dir = std::env::temp_dir().join(format!("aoc-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        let client =
+            Client::new(&server.url(), Some(SESSION.to_string())).with_cache_dir(&cache_dir);
+
+        for _ in 0..2 {
+            assert_eq!(
+                client.fetch_input(2022, 3),
+                Ok("input of 2022 day 3\n".to_string())
+            );
+        }
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(
+            fs::read_to_string(cache_dir.join("2022").join("input_day03.txt")).unwrap(),
+            "input of 2022 day 3\n"
+        );
+
+        // Failed downloads are not cached
+        assert!(client.fetch_input(2022, LOCKED_FROM).is_err());
+        assert!(client.fetch_input(2022, LOCKED_FROM).is_err());
+        assert_eq!(server.requests().len(), 3);
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_post_answer() {
+        let server = MockServer::start();
+        let client = Client::new(&format!("{}/", server.url()), Some(SESSION.to_string()));
+
+        let page = client.post_answer(2023, 4, Part::B, "42").unwrap();
+        assert!(page.contains("That's the right answer!"));
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2023/day/4/answer");
+        assert_eq!(requests[0].body, "level=2&answer=42");
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let server = MockServer::start();
+
+        let client = Client::new(&server.url(), Some("expired".to_string()));
+        assert_eq!(client.fetch_input(2023, 4), Err(FetchError::ExpiredSession));
+        // Without a valid session the page only has part one
+        let page = client.fetch_puzzle(2023, 4).unwrap();
+        assert!(!page.contains("--- Part Two ---"));
+
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        assert_eq!(client.fetch_input(2023, 25), Err(FetchError::NotUnlocked));
+        assert_eq!(client.fetch_puzzle(2023, 25), Err(FetchError::NotUnlocked));
+        assert_eq!(
+            client.fetch_input(SERVER_ERROR_YEAR, 1),
+            Err(FetchError::ServerError(500))
+        );
+
+        let client = Client::new(&server.url(), None);
+        assert_eq!(client.fetch_input(2023, 4), Err(FetchError::NoSession));
+        assert_eq!(server.requests().len(), 5);
+
+        // Nothing listens on the port anymore
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let client = Client::new(&format!("http://{}", address), Some(SESSION.to_string()));
+        assert!(matches!(
+            client.fetch_input(2023, 4),
+            Err(FetchError::Transport(_))
+        ));
+    }
+}
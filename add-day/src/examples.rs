@@ -0,0 +1,193 @@
+// Extracts the example input and the expected answers from the puzzle page, so they can be
+// used in the generated tests. The description doesn't mark which block is the example or
+// which number is the answer, so every guess that could be wrong is reported as a TODO.
+
+use crate::markdown::{extract_articles, tokenize, Token};
+
+/// The code blocks and emphasized code of one part of the puzzle description.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Part {
+    blocks: Vec<String>,
+    emphasized: Vec<String>,
+}
+
+impl Part {
+    fn parse(article: &str) -> Self {
+        let mut part = Self::default();
+        let mut in_pre = false;
+        let mut in_code = false;
+        let mut in_em = false;
+        let mut text = String::new();
+
+        for token in tokenize(article) {
+            match token {
+                Token::Open("pre", _) => {
+                    in_pre = true;
+                    text.clear();
+                }
+                Token::Close("pre") => {
+                    in_pre = false;
+                    part.blocks.push(text.trim_end_matches('\n').to_string());
+                }
+                Token::Open("code", _) => {
+                    in_code = true;
+                    if !in_pre && in_em {
+                        text.clear();
+                    }
+                }
+                Token::Open("em", _) => {
+                    in_em = true;
+                    if !in_pre && in_code {
+                        text.clear();
+                    }
+                }
+                // Answers are written as <code><em>42</em></code>, sometimes the other way round
+                Token::Close(tag @ ("code" | "em")) => {
+                    if !in_pre && in_code && in_em {
+                        part.emphasized.push(text.trim().to_string());
+                    }
+                    if tag == "code" {
+                        in_code = false;
+                    } else {
+                        in_em = false;
+                    }
+                }
+                Token::Text(content) if in_pre || (in_code && in_em) => text.push_str(&content),
+                _ => {}
+            }
+        }
+
+        part
+    }
+
+    /// The answer of the example, which is the last emphasized code of the part.
+    fn answer(&self) -> Option<&str> {
+        self.emphasized.last().map(|answer| answer.as_str())
+    }
+}
+
+/// The example of the puzzle with everything that has to be checked by hand.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Examples {
+    pub input: Option<String>,
+    pub answer_a: Option<i64>,
+    pub answer_b: Option<i64>,
+    pub todos: Vec<String>,
+}
+
+fn parse_answer(part: &Part, name: &str, todos: &mut Vec<String>) -> Option<i64> {
+    let Some(answer) = part.answer() else {
+        todos.push(format!("add the expected answer of part {}", name));
+        return None;
+    };
+    match answer.parse() {
+        Ok(answer) => Some(answer),
+        Err(_) => {
+            todos.push(format!(
+                "the answer of part {} looks like {}, which isn't a number",
+                name, answer
+            ));
+            None
+        }
+    }
+}
+
+pub fn extract_examples(html: &str) -> Examples {
+    let parts = extract_articles(html)
+        .into_iter()
+        .map(Part::parse)
+        .collect::<Vec<_>>();
+    let mut examples = Examples::default();
+
+    let Some(part_a) = parts.first() else {
+        examples
+            .todos
+            .push("add the example from the puzzle description".to_string());
+        return examples;
+    };
+
+    examples.input = part_a.blocks.first().cloned();
+    match part_a.blocks.len() {
+        0 => examples
+            .todos
+            .push("add the example from the puzzle description".to_string()),
+        1 => {}
+        blocks => examples.todos.push(format!(
+            "the description has {} code blocks, check that the first one is the example",
+            blocks
+        )),
+    }
+    examples.answer_a = parse_answer(part_a, "one", &mut examples.todos);
+
+    match parts.get(1) {
+        Some(part_b) => {
+            if !part_b.blocks.is_empty() {
+                examples
+                    .todos
+                    .push("part two has its own example, see README.md".to_string());
+            }
+            examples.answer_b = parse_answer(part_b, "two", &mut examples.todos);
+        }
+        None => examples
+            .todos
+            .push("add the expected answer of part two once it is unlocked".to_string()),
+    }
+
+    examples
+}
+
+/// Formats the example input as a string literal like the ones in the existing tests.
+pub fn string_literal(input: &str) -> String {
+    format!("\"{}\"", input.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = include_str!("../fixtures/puzzle_page.html");
+
+    #[test]
+    fn test_extract_examples() {
+        assert_eq!(
+            extract_examples(PAGE),
+            Examples {
+                input: Some("3 lit\n5 dark\n<11> & more".to_string()),
+                answer_a: Some(8),
+                answer_b: Some(5),
+                todos: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_extract_ambiguous_examples() {
+        let page = r#"<article class="day-desc"><h2>--- Day 9: Test ---</h2>
+<pre><code>1 2</code></pre><p>becomes</p><pre><code>3</code></pre>
+<p>So the answer is <em><code>-3</code></em>.</p></article>"#;
+        let examples = extract_examples(page);
+        assert_eq!(examples.input, Some("1 2".to_string()));
+        assert_eq!(examples.answer_a, Some(-3));
+        assert_eq!(examples.answer_b, None);
+        assert_eq!(examples.todos.len(), 2);
+        assert!(examples.todos[0].contains("2 code blocks"));
+
+        let page = r#"<article class="day-desc"><p>The code is <code><em>ABC</em></code>.</p>
+</article><article class="day-desc"><pre><code>x</code></pre></article>"#;
+        let examples = extract_examples(page);
+        assert_eq!(examples.input, None);
+        assert_eq!(examples.answer_a, None);
+        assert_eq!(examples.todos.len(), 4);
+
+        assert_eq!(extract_examples("").todos.len(), 1);
+    }
+
+    #[test]
+    fn test_string_literal() {
+        assert_eq!(
+            string_literal("a\\b\n\"c\""),
+            r#""a\\b
+\"c\"""#
+        );
+    }
+}
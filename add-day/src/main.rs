@@ -1,144 +1,930 @@
+use std::fmt;
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
 use std::path::PathBuf;
 
+use aoc_utils::{day_directory, DEFAULT_YEAR};
 use dotenvy::dotenv;
 
-const AOC_YEAR: &str = "2023";
+use client::Client;
+use session::SessionOptions;
+use submit::SubmitOptions;
+use unlock::{Clock, SystemClock};
 
-// This is a utility that asks the user for a input number
-// and then creates a new crate for the advent of code challenge of that day.
+mod client;
+mod examples;
+mod markdown;
+#[cfg(test)]
+mod mock_server;
+mod session;
+mod submit;
+mod unlock;
+
+const PART_TWO_HEADING: &str = "## Part Two";
+const DEFAULT_TEMPLATE: &str = "default";
+const TEMPLATE_EXTENSION: &str = ".rs.tmpl";
+const USAGE: &str =
+    "Usage: add-day [<day>] [--year <year>] [--template <name>] [--no-fetch] [--force] [--dry-run]
+              [--wait]
+       add-day submit <day> <part> <answer> [--year <year>]
+       add-day session <set|check>
+
+Creates a new crate for the advent of code challenge of the given day.
+If no day is given, it will be asked for interactively.
+Days of 2023 are created as dayNN, days of other years as YYYY/dayNN.
+If the day already exists, only the missing parts are created and an empty input is fetched again.
+
+Options:
+    --year <year>  Year of the puzzle (default: AOC_YEAR from the environment or .env, else 2023)
+    --template <name>
+                   Template for src/main.rs from add-day/templates, e.g. grid, sections or i64
+                   (default: default)
+    --no-fetch     Don't download the puzzle input, create an empty input file instead
+    --force        Overwrite the generated files and the input even if they already exist
+    --dry-run      Only print what would be done
+    --wait         Wait until the puzzle unlocks at midnight EST before creating the day
+
+Use add-day submit --help for the options of submitting answers
+and add-day session --help for storing the session cookie.";
+
+// This is a utility that creates a new crate for the advent of code challenge of a given day.
 // Additionally it will download the puzzle input for that day and store it in the
 // newly created crate, if the session cookie is provided in the AOC_SESSION env variable or in the .env file.
+// Running it again for an existing day repairs it by only creating the parts that are missing,
+// so solution code is never overwritten unless --force is given.
+// The year can be given with --year or set once with the AOC_YEAR env variable or in the .env file.
+// The generated main.rs comes from one of the templates in add-day/templates, in which
+// placeholders like {{day}}, {{year}} or {{title}} are replaced.
+// The submit subcommand submits an answer, see the submit module. The session subcommand stores
+// the session cookie in the user config instead, see the session module.
+// All requests go through the client module, whose base URL can be set with AOC_BASE_URL.
+// With a session the puzzle description is saved as README.md too, and fetched again
+// on later runs until it contains part two. The example and its answers are taken from the
+// description for the generated tests, with a TODO for everything that has to be checked.
+// With --wait it can be started before the puzzle unlocks, see the unlock module.
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    day: Option<u8>,
+    year: Option<u16>,
+    template: String,
+    fetch: bool,
+    force: bool,
+    dry_run: bool,
+    wait: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Create(Options),
+    Submit(SubmitOptions),
+    Session(SessionOptions),
+}
+
+impl Command {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.peekable();
+        match args.peek().map(String::as_str) {
+            Some("submit") => SubmitOptions::parse(args.skip(1)).map(Command::Submit),
+            Some("session") => SessionOptions::parse(args.skip(1)).map(Command::Session),
+            _ => Options::parse(args).map(Command::Create),
+        }
+    }
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            day: None,
+            year: None,
+            template: DEFAULT_TEMPLATE.to_string(),
+            fetch: true,
+            force: false,
+            dry_run: false,
+            wait: false,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--year" => {
+                    let year = args.next().ok_or("Missing value for --year")?;
+                    options.year = Some(parse_year(&year)?);
+                }
+                "--template" => {
+                    options.template = args.next().ok_or("Missing value for --template")?;
+                }
+                "--no-fetch" => options.fetch = false,
+                "--force" => options.force = true,
+                "--dry-run" => options.dry_run = true,
+                "--wait" => options.wait = true,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if options.day.is_some() => return Err(format!("Unexpected argument: {}", arg)),
+                _ => options.day = Some(parse_day(&arg)?),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_day(s: &str) -> Result<u8, String> {
+    match s.trim().parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "Invalid day {}, has to be a number from 1 to 25",
+            s.trim()
+        )),
+    }
+}
+
+fn parse_year(s: &str) -> Result<u16, String> {
+    match s.trim().parse::<u16>() {
+        // The first advent of code was in 2015
+        Ok(year) if year >= 2015 => Ok(year),
+        _ => Err(format!("Invalid year {}", s.trim())),
+    }
+}
+
+/// The year to use if none was given on the command line.
+fn year_from_env() -> Result<u16, String> {
+    match std::env::var("AOC_YEAR") {
+        Ok(year) => parse_year(&year).map_err(|e| format!("{} in AOC_YEAR", e)),
+        Err(_) => Ok(DEFAULT_YEAR),
+    }
+}
+
+/// The name of the crate of a day. Days of other years than the [`DEFAULT_YEAR`] get the year
+/// in their name too, because crate names have to be unique within the workspace.
+fn crate_name(year: u16, day: u8) -> String {
+    if year == DEFAULT_YEAR {
+        format!("day{:02}", day)
+    } else {
+        format!("y{}d{:02}", year, day)
+    }
+}
 
 fn main() {
     dotenv().ok();
 
-    let day = get_day();
-    let day_dir = create_day_dir(day);
-    create_cargo_toml(day, &day_dir);
-    create_src(day, &day_dir);
-    create_input_file(day, &day_dir);
+    let command = Command::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
+    let root = Path::new(".");
+    let client = Client::from_env();
+    if !matches!(command, Command::Session(_)) {
+        session::warn_if_expiring(&client, std::time::SystemTime::now());
+    }
+    let result = match command {
+        Command::Create(options) => run(&options, root, &client, &SystemClock),
+        Command::Submit(options) => submit::run_submit(&options, root, &client),
+        Command::Session(options) => session::run_session(
+            &options,
+            session::config_path().as_deref(),
+            client,
+            std::time::SystemTime::now(),
+        ),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// A single part of creating a day.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    CreateDir(PathBuf),
+    WriteCargoToml(PathBuf),
+    WriteMainRs(PathBuf),
+    WriteReadme(PathBuf),
+    FetchInput(PathBuf),
+    CreateEmptyInput(PathBuf),
+    AddWorkspaceMember(PathBuf, String),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateDir(path) => write!(f, "Create directory {}", path.display()),
+            Self::WriteCargoToml(path) | Self::WriteMainRs(path) | Self::WriteReadme(path) => {
+                write!(f, "Write {}", path.display())
+            }
+            Self::FetchInput(path) => write!(f, "Fetch puzzle input into {}", path.display()),
+            Self::CreateEmptyInput(path) => write!(f, "Create empty {}", path.display()),
+            Self::AddWorkspaceMember(path, member) => {
+                write!(f, "Add {} to the members in {}", member, path.display())
+            }
+        }
+    }
+}
+
+/// Determines the steps needed to create the given day inside of `root`, skipping everything
+/// that already exists. Existing files are only overwritten if `force` is set.
+fn plan(root: &Path, year: u16, day: u8, can_fetch: bool, force: bool) -> Vec<Step> {
+    let day_dir = root.join(day_directory(year, day));
+    let cargo_toml = day_dir.join("Cargo.toml");
+    let main_rs = day_dir.join("src").join("main.rs");
+    let readme = day_dir.join("README.md");
+    let input = day_dir.join(format!("input_day{:02}.txt", day));
+
+    let mut steps = Vec::new();
+    // Cargo fails on member globs that don't match anything, so every year other than the
+    // default one is added to the workspace once its first day exists
+    let workspace = root.join("Cargo.toml");
+    let member = format!("\"{}/day*\"", year);
+    if year != DEFAULT_YEAR {
+        if let Ok(manifest) = fs::read_to_string(&workspace) {
+            if !manifest.contains(&member) {
+                steps.push(Step::AddWorkspaceMember(workspace, member));
+            }
+        }
+    }
+    if !day_dir.is_dir() {
+        steps.push(Step::CreateDir(day_dir));
+    }
+    if force || !cargo_toml.exists() {
+        steps.push(Step::WriteCargoToml(cargo_toml));
+    }
+    if force || !main_rs.exists() {
+        steps.push(Step::WriteMainRs(main_rs));
+    }
+    // Part two is only part of the description once part one is solved
+    let has_part_two =
+        fs::read_to_string(&readme).is_ok_and(|content| content.contains(PART_TWO_HEADING));
+    if can_fetch && (force || !has_part_two) {
+        steps.push(Step::WriteReadme(readme));
+    }
+
+    let input_empty = fs::metadata(&input).map_or(true, |m| m.len() == 0);
+    if can_fetch && (force || input_empty) {
+        steps.push(Step::FetchInput(input));
+    } else if !input.exists() {
+        steps.push(Step::CreateEmptyInput(input));
+    }
+
+    steps
+}
+
+/// The contents of the generated files that come from the template or the puzzle page.
+struct Generated {
+    main_rs: String,
+    readme: Option<String>,
+}
+
+fn execute(
+    step: &Step,
+    year: u16,
+    day: u8,
+    generated: &Generated,
+    client: &Client,
+) -> Result<(), String> {
+    match step {
+        Step::CreateDir(path) => fs::create_dir_all(path)
+            .map_err(|e| format!("Could not create {}: {}", path.display(), e)),
+        Step::WriteCargoToml(path) => write_file(path, &cargo_toml(year, day)),
+        Step::WriteMainRs(path) => write_file(path, &generated.main_rs),
+        Step::WriteReadme(path) => match &generated.readme {
+            Some(readme) => write_file(path, readme),
+            None => Err("The puzzle description was not fetched".to_string()),
+        },
+        Step::FetchInput(path) => {
+            // Only written after a successful download, so a failure leaves no partial input
+            let input = client.fetch_input(year, day).map_err(|e| e.to_string())?;
+            write_file(path, &input)
+        }
+        Step::CreateEmptyInput(path) => write_file(path, ""),
+        Step::AddWorkspaceMember(path, member) => add_workspace_member(path, member),
+    }
+}
+
+fn add_workspace_member(path: &Path, member: &str) -> Result<(), String> {
+    let manifest = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let start = manifest
+        .find("members = [")
+        .ok_or_else(|| format!("No workspace members found in {}", path.display()))?;
+    let end = start
+        + manifest[start..]
+            .find(']')
+            .ok_or_else(|| format!("Unterminated workspace members in {}", path.display()))?;
+
+    let members = manifest[..end].trim_end();
+    let separator = if members.ends_with(',') || members.ends_with('[') {
+        ""
+    } else {
+        ","
+    };
+    let updated = format!(
+        "{}{}\n    {},\n{}",
+        members,
+        separator,
+        member,
+        &manifest[end..]
+    );
+    write_file(path, &updated)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Creates or repairs the day inside of the workspace at `root`.
+/// With --wait, the clock is used to wait for the unlock.
+fn run(options: &Options, root: &Path, client: &Client, clock: &dyn Clock) -> Result<(), String> {
+    let day = match options.day {
+        Some(day) => day,
+        None => ask_for_day()?,
+    };
+    let year = match options.year {
+        Some(year) => year,
+        None => year_from_env()?,
+    };
+
+    // Loaded up front, so a missing template is reported before anything is created
+    let template = load_template(&templates_dir(), &options.template)?;
+
+    if options.fetch && !client.has_session() {
+        println!("No AOC_SESSION environment variable or stored session found. Puzzle input will not be automatically fetched. Refer to .env.example or run add-day session set");
+    }
+
+    if options.wait && !options.dry_run {
+        unlock::wait_for_unlock(clock, year, day);
+    }
+
+    let can_fetch = options.fetch && client.has_session();
+    let mut steps = plan(root, year, day, can_fetch, options.force);
+    if steps.is_empty() {
+        println!(
+            "{} is already complete, nothing to do",
+            day_directory(year, day).display()
+        );
+        return Ok(());
+    }
+
+    // The puzzle page provides the README and the title for main.rs. With --wait it's fetched
+    // in any case, because it also shows that the puzzle is unlocked and the input can be fetched.
+    let needs_puzzle = options.wait
+        || steps
+            .iter()
+            .any(|step| matches!(step, Step::WriteMainRs(_) | Step::WriteReadme(_)));
+    let mut puzzle = None;
+    if can_fetch && needs_puzzle && !options.dry_run {
+        let fetched = if options.wait {
+            unlock::retry_until_unlocked(clock, || client.fetch_puzzle(year, day))
+        } else {
+            client.fetch_puzzle(year, day)
+        };
+        match fetched {
+            Ok(html) => puzzle = Some(html),
+            Err(e) => {
+                println!("{}, skipping README.md", e);
+                steps.retain(|step| !matches!(step, Step::WriteReadme(_)));
+            }
+        }
+    }
+    let generated = Generated {
+        main_rs: render_template(
+            &template,
+            &template_variables(year, day, puzzle.as_deref(), returns_signed(&template)),
+        )?,
+        readme: puzzle.as_deref().map(markdown::puzzle_to_markdown),
+    };
+
+    for step in steps.iter() {
+        println!("{}", step);
+        if !options.dry_run {
+            execute(step, year, day, &generated, client)?;
+        }
+    }
+    Ok(())
 }
 
-fn get_day() -> u8 {
+fn ask_for_day() -> Result<u8, String> {
     print!("Please enter the day you want to create a crate for: ");
-    io::stdout().flush().unwrap();
+    io::stdout().flush().map_err(|e| e.to_string())?;
 
     let mut day = String::new();
     io::stdin()
         .read_line(&mut day)
-        .expect("Failed to read line");
-    day.trim().parse::<u8>().expect("Please enter a number!")
+        .map_err(|e| format!("Failed to read day: {}", e))?;
+    parse_day(&day)
 }
 
-fn create_day_dir(day: u8) -> PathBuf {
-    let day_directory_str = format!("day{:0>2}", day);
-    let day_directory = PathBuf::from(&day_directory_str);
-    fs::create_dir(&day_directory).expect("Could not create day directory");
-
-    day_directory
-}
-
-fn create_cargo_toml(day: u8, day_dir: &Path) {
-    let cargo_toml_path = day_dir.join("Cargo.toml");
-    let cargo_toml_str = format!(
+fn cargo_toml(year: u16, day: u8) -> String {
+    // Days of other years are nested one directory deeper
+    let utils_path = if year == DEFAULT_YEAR {
+        "../aoc-utils"
+    } else {
+        "../../aoc-utils"
+    };
+    format!(
         r#"[package]
-name = "day{:02}"
+name = "{}"
 version = "0.1.0"
 edition = "2021"
 
 [dependencies]
-aoc-utils = {{ path = "../aoc-utils" }}
+aoc-utils = {{ path = "{}" }}
 "#,
-        day
-    );
-    fs::write(cargo_toml_path, cargo_toml_str).expect("Could not write Cargo.toml");
+        crate_name(year, day),
+        utils_path
+    )
+}
+
+fn templates_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("templates")
 }
 
-fn create_src(day: u8, day_dir: &Path) {
-    let src_dir = &day_dir.join("src");
-    fs::create_dir(src_dir).expect("Could not create src directory");
+/// Reads the template with the given name, listing the available ones if it doesn't exist.
+fn load_template(dir: &Path, name: &str) -> Result<String, String> {
+    let path = dir.join(format!("{}{}", name, TEMPLATE_EXTENSION));
+    fs::read_to_string(&path).map_err(|_| {
+        let mut available = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.ok()?.file_name().into_string().ok()?;
+                file_name
+                    .strip_suffix(TEMPLATE_EXTENSION)
+                    .map(|name| name.to_string())
+            })
+            .collect::<Vec<_>>();
+        available.sort();
+        format!(
+            "Unknown template {}, available templates: {}",
+            name,
+            available.join(", ")
+        )
+    })
+}
 
-    let main_rs_path = src_dir.join("main.rs");
-    let main_rs_str = format!(
-        r#"use aoc_utils::PuzzleInput;
-const DAY: u8 = {day};
+/// Whether `solve_a` of the template returns a signed integer like `i64`, so a negative
+/// example answer can be asserted.
+fn returns_signed(template: &str) -> bool {
+    template
+        .split_once("fn solve_a(")
+        .and_then(|(_, rest)| rest.split_once("-> "))
+        .is_some_and(|(_, return_type)| return_type.starts_with('i'))
+}
 
-fn main() {{
-    let input = PuzzleInput::get_input(DAY);
-    println!("A: {{}}", solve_a(&input));
-    println!("B: {{}}", solve_b(&input));
-}}
+/// The values for the placeholders that can be used in templates. The title and the example
+/// are taken from the puzzle page if it was fetched. Negative example answers are only filled
+/// in if the template is `signed`, see [`returns_signed`].
+fn template_variables(
+    year: u16,
+    day: u8,
+    puzzle: Option<&str>,
+    signed: bool,
+) -> Vec<(&'static str, String)> {
+    let title = puzzle
+        .and_then(markdown::extract_title)
+        .unwrap_or_else(|| format!("Day {}", day));
+    let mut examples = examples::extract_examples(puzzle.unwrap_or_default());
+    for (part, answer) in [("A", &mut examples.answer_a), ("B", &mut examples.answer_b)] {
+        if let Some(negative) = answer.filter(|answer| *answer < 0 && !signed) {
+            examples.todos.push(format!(
+                "the answer {} of part {} is negative, but the template returns an unsigned number",
+                negative, part
+            ));
+            *answer = None;
+        }
+    }
+    let example_comment = if examples.todos.is_empty() {
+        "Example from the puzzle description".to_string()
+    } else {
+        format!("TODO: {}", examples.todos.join("; "))
+    };
+    let answer = |answer: Option<i64>| answer.unwrap_or(0).to_string();
+
+    vec![
+        ("day", day.to_string()),
+        ("day_padded", format!("{:02}", day)),
+        ("year", year.to_string()),
+        ("title", title),
+        ("crate_name", crate_name(year, day)),
+        ("example_comment", example_comment),
+        (
+            "example_input",
+            examples::string_literal(examples.input.as_deref().unwrap_or_default()),
+        ),
+        ("answer_a", answer(examples.answer_a)),
+        ("answer_b", answer(examples.answer_b)),
+    ]
+}
 
-fn solve_a(input: &PuzzleInput) -> usize {{
-    input.lines().count()
-}}
+/// Replaces every `{{name}}` placeholder with its value. Braces that don't enclose a plain
+/// name are left alone, but an unknown name is an error to catch typos in templates.
+fn render_template(template: &str, variables: &[(&str, String)]) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
 
-fn solve_b(input: &PuzzleInput) -> usize {{
-    input.lines().count()
-}}
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let name = after_open
+            .find("}}")
+            .map(|end| &after_open[..end])
+            .filter(|name| {
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+
+        match name {
+            Some(name) => {
+                let value = variables
+                    .iter()
+                    .find(|(variable, _)| *variable == name)
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| format!("Unknown template variable {{{{{}}}}}", name))?;
+                result.push_str(value);
+                rest = &after_open[name.len() + 2..];
+            }
+            None => {
+                result.push_str("{{");
+                rest = after_open;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
 
 #[cfg(test)]
-mod tests {{
+mod tests {
     use super::*;
+    use client::FetchError;
+    use mock_server::{MockServer, LOCKED_FROM, SESSION};
+    use unlock::tests::FakeClock;
 
-    const TEST_INPUT: &str = "";
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|s| s.to_string()))
+    }
 
     #[test]
-    fn test_no_panic() {{
-        let input = PuzzleInput::get_input(DAY);
-        solve_a(&input);
-        solve_b(&input);
-    }}
+    fn test_parse_options() {
+        assert_eq!(
+            parse(&["10"]),
+            Ok(Options {
+                day: Some(10),
+                year: None,
+                template: "default".to_string(),
+                fetch: true,
+                force: false,
+                dry_run: false,
+                wait: false
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "--year",
+                "2024",
+                "3",
+                "--template",
+                "grid",
+                "--no-fetch",
+                "--force",
+                "--dry-run",
+                "--wait"
+            ]),
+            Ok(Options {
+                day: Some(3),
+                year: Some(2024),
+                template: "grid".to_string(),
+                fetch: false,
+                force: true,
+                dry_run: true,
+                wait: true
+            })
+        );
+        assert_eq!(parse(&[]).unwrap().day, None);
+    }
 
     #[test]
-    fn test_solve_a() {{
-        assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 0);
-    }}
+    fn test_parse_command() {
+        let parse = |args: &[&str]| Command::parse(args.iter().map(|s| s.to_string()));
+        assert!(matches!(
+            parse(&["submit", "1", "a", "42"]),
+            Ok(Command::Submit(_))
+        ));
+        assert!(matches!(
+            parse(&["session", "check"]),
+            Ok(Command::Session(SessionOptions::Check))
+        ));
+        assert!(matches!(parse(&["1"]), Ok(Command::Create(_))));
+        assert!(parse(&["submit", "1"]).is_err());
+    }
 
     #[test]
-    fn test_solve_b() {{
-        assert_eq!(solve_b(&PuzzleInput::new(TEST_INPUT)), 0);
-    }}
-}}
-"#,
-        day = day
-    );
-    fs::write(main_rs_path, main_rs_str).expect("Could not write main.rs");
-}
+    fn test_parse_invalid_options() {
+        assert!(parse(&["0"]).is_err());
+        assert!(parse(&["26"]).is_err());
+        assert!(parse(&["ten"]).is_err());
+        assert!(parse(&["1", "2"]).is_err());
+        assert!(parse(&["--year"]).is_err());
+        assert!(parse(&["--year", "1999", "1"]).is_err());
+        assert!(parse(&["--template"]).is_err());
+        assert!(parse(&["--foo"]).is_err());
+    }
 
-fn create_input_file(day: u8, day_dir: &Path) {
-    let name = format!("input_day{:02}.txt", day);
-    let input_file_path = day_dir.join(name);
-    let mut file = fs::File::create(input_file_path).expect("Could not create input file");
+    fn generated(main_rs: &str) -> Generated {
+        Generated {
+            main_rs: main_rs.to_string(),
+            readme: None,
+        }
+    }
 
-    let input_content = fetch_input(day);
-    let input_content = input_content.as_bytes();
-    file.write_all(input_content)
-        .expect("Could not write input file");
-}
+    fn offline() -> Client {
+        Client::new(client::DEFAULT_BASE_URL, None)
+    }
 
-fn fetch_input(day: u8) -> String {
-    let session = std::env::var("AOC_SESSION").ok();
-    if session.is_none() {
-        println!("No AOC_SESSION environment variable found. Puzzle input will not be automatically fetched. Refer to .env.sample");
-        return String::new();
+    /// Creates an empty directory that is unique to the test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("add-day-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
     }
-    let session = session.unwrap();
 
-    let url = format!("https://adventofcode.com/{AOC_YEAR}/day/{day}/input");
-    println!("Fetching input from {}...", url);
-    let response = ureq::get(&url)
-        .set("Cookie", &format!("session={}", session))
-        .call()
-        .expect("Could not fetch input");
+    #[test]
+    fn test_plan_new_day() {
+        let root = test_dir("plan-new");
+        let day_dir = root.join("day07");
+        assert_eq!(
+            plan(&root, 2023, 7, false, false),
+            vec![
+                Step::CreateDir(day_dir.clone()),
+                Step::WriteCargoToml(day_dir.join("Cargo.toml")),
+                Step::WriteMainRs(day_dir.join("src").join("main.rs")),
+                Step::CreateEmptyInput(day_dir.join("input_day07.txt")),
+            ]
+        );
+        assert_eq!(
+            plan(&root, 2023, 7, true, false).last(),
+            Some(&Step::FetchInput(day_dir.join("input_day07.txt")))
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_plan_repairs_partial_day() {
+        let root = test_dir("plan-partial");
+        let day_dir = root.join("day08");
+        for step in plan(&root, 2023, 8, false, false) {
+            execute(&step, 2023, 8, &generated(""), &offline()).unwrap();
+        }
+        fs::write(day_dir.join("src").join("main.rs"), "// my solution").unwrap();
+        fs::remove_file(day_dir.join("Cargo.toml")).unwrap();
 
-    if response.status() != 200 {
-        panic!("Could not fetch input");
+        // The empty input is fetched again, the solution is left alone
+        assert_eq!(
+            plan(&root, 2023, 8, true, false),
+            vec![
+                Step::WriteCargoToml(day_dir.join("Cargo.toml")),
+                Step::WriteReadme(day_dir.join("README.md")),
+                Step::FetchInput(day_dir.join("input_day08.txt")),
+            ]
+        );
+        assert_eq!(
+            plan(&root, 2023, 8, false, false),
+            vec![Step::WriteCargoToml(day_dir.join("Cargo.toml"))]
+        );
+
+        execute(
+            &Step::WriteCargoToml(day_dir.join("Cargo.toml")),
+            2023,
+            8,
+            &generated(""),
+            &offline(),
+        )
+        .unwrap();
+        fs::write(day_dir.join("input_day08.txt"), "1 2 3").unwrap();
+
+        // The description is fetched again until part two is unlocked
+        let readme = Step::WriteReadme(day_dir.join("README.md"));
+        let mut page = generated("");
+        page.readme = Some("## Day 8: Test\n\nPart one\n".to_string());
+        execute(&readme, 2023, 8, &page, &offline()).unwrap();
+        assert_eq!(plan(&root, 2023, 8, true, false), vec![readme]);
+        fs::write(day_dir.join("README.md"), "## Day 8\n\n## Part Two\n").unwrap();
+        assert_eq!(plan(&root, 2023, 8, true, false), vec![]);
+        assert_eq!(
+            fs::read_to_string(day_dir.join("src").join("main.rs")).unwrap(),
+            "// my solution"
+        );
+
+        assert_eq!(plan(&root, 2023, 8, true, true).len(), 4);
+        fs::remove_dir_all(root).unwrap();
     }
 
-    println!("Input fetched successfully!");
-    response.into_string().expect("Could not parse input")
+    fn create_options(day: u8) -> Options {
+        Options {
+            day: Some(day),
+            year: Some(2023),
+            template: DEFAULT_TEMPLATE.to_string(),
+            fetch: true,
+            force: false,
+            dry_run: false,
+            wait: false,
+        }
+    }
+
+    #[test]
+    fn test_run_with_mock_server() {
+        let server = MockServer::start();
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        let root = test_dir("run-mock");
+        let day_dir = root.join("day04");
+
+        run(&create_options(4), &root, &client, &SystemClock).unwrap();
+        assert_eq!(
+            fs::read_to_string(day_dir.join("input_day04.txt")).unwrap(),
+            "input of 2023 day 4\n"
+        );
+        let readme = fs::read_to_string(day_dir.join("README.md")).unwrap();
+        assert!(readme.starts_with("## Day 4: Lantern & Ladder\n"));
+        assert!(readme.contains(PART_TWO_HEADING));
+        let main_rs = fs::read_to_string(day_dir.join("src").join("main.rs")).unwrap();
+        assert!(main_rs.contains("Day 4: Lantern & Ladder"));
+        assert!(main_rs.contains("assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 8);"));
+
+        // Nothing left to fetch on the second run
+        let requests = server.requests().len();
+        run(&create_options(4), &root, &client, &SystemClock).unwrap();
+        assert_eq!(server.requests().len(), requests);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_run_with_failing_requests() {
+        let server = MockServer::start();
+        let root = test_dir("run-failing");
+
+        // Not unlocked yet: the day is created, but without input and description
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        let error = run(&create_options(LOCKED_FROM), &root, &client, &SystemClock).unwrap_err();
+        assert_eq!(error, FetchError::NotUnlocked.to_string());
+        let day_dir = root.join(format!("day{}", LOCKED_FROM));
+        assert!(day_dir.join("src").join("main.rs").exists());
+        assert!(!day_dir.join("input_day20.txt").exists());
+        assert!(!day_dir.join("README.md").exists());
+
+        // An expired session still gets part one of the description, but no input
+        let client = Client::new(&server.url(), Some("expired".to_string()));
+        let error = run(&create_options(5), &root, &client, &SystemClock).unwrap_err();
+        assert_eq!(error, FetchError::ExpiredSession.to_string());
+        let day_dir = root.join("day05");
+        assert!(day_dir.join("README.md").exists());
+        assert!(!day_dir.join("input_day05.txt").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_run_with_wait() {
+        let server = MockServer::start();
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        let root = test_dir("run-wait");
+        let mut options = create_options(6);
+        options.wait = true;
+
+        let unlock = unlock::unlock_time(2023, 6);
+        let clock = FakeClock::new(unlock - std::time::Duration::from_secs(90));
+        run(&options, &root, &client, &clock).unwrap();
+        assert_eq!(clock.now(), unlock);
+        assert!(root.join("day06").join("input_day06.txt").exists());
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/2023/day/6", "/2023/day/6/input"]);
+
+        // A puzzle that stays locked is retried with backoff before giving up
+        options.day = Some(LOCKED_FROM);
+        let clock = FakeClock::new(unlock::unlock_time(2023, LOCKED_FROM));
+        let error = run(&options, &root, &client, &clock).unwrap_err();
+        assert_eq!(error, FetchError::NotUnlocked.to_string());
+        assert!(clock.sleeps.borrow().len() > 1);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_other_year_layout() {
+        let root = test_dir("other-year");
+        let day_dir = root.join("2024").join("day03");
+        let workspace = root.join("Cargo.toml");
+        fs::write(&workspace, "[workspace]\n\nmembers = [\n    \"day*\",\n]\n").unwrap();
+        let template = load_template(&templates_dir(), DEFAULT_TEMPLATE).unwrap();
+        let main_rs =
+            render_template(&template, &template_variables(2024, 3, None, false)).unwrap();
+        for step in plan(&root, 2024, 3, false, false) {
+            execute(&step, 2024, 3, &generated(&main_rs), &offline()).unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(&workspace).unwrap(),
+            "[workspace]\n\nmembers = [\n    \"day*\",\n    \"2024/day*\",\n]\n"
+        );
+        assert_eq!(
+            plan(&root, 2024, 4, false, false)[0],
+            Step::CreateDir(root.join("2024").join("day04"))
+        );
+        assert_eq!(plan(&root, 2023, 4, false, false).len(), 4);
+
+        let cargo_toml = fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains(r#"name = "y2024d03""#));
+        assert!(cargo_toml.contains(r#"path = "../../aoc-utils""#));
+        let main_rs = fs::read_to_string(day_dir.join("src").join("main.rs")).unwrap();
+        assert!(main_rs.contains("const YEAR: u16 = 2024;"));
+        assert!(day_dir.join("input_day03.txt").exists());
+        assert!(!root.join("day03").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_render_template() {
+        let variables = template_variables(2024, 3, None, false);
+        assert_eq!(
+            render_template("day{{day_padded}} of {{ year }}: {{title}}", &variables),
+            Ok("day03 of {{ year }}: Day 3".to_string())
+        );
+        let page = "<article class=\"day-desc\"><h2>--- Day 3: Gears ---</h2></article>";
+        assert_eq!(
+            render_template("{{title}}", &template_variables(2024, 3, Some(page), false)),
+            Ok("Day 3: Gears".to_string())
+        );
+        assert_eq!(
+            render_template(r#"println!("{{}}", {{day}}); {{"#, &variables),
+            Ok(r#"println!("{{}}", 3); {{"#.to_string())
+        );
+        assert!(render_template("{{days}}", &variables).is_err());
+    }
+
+    #[test]
+    fn test_templates() {
+        for name in ["default", "grid", "sections", "i64"] {
+            let template = load_template(&templates_dir(), name).unwrap();
+            let main_rs =
+                render_template(&template, &template_variables(2015, 25, None, false)).unwrap();
+            assert!(main_rs.contains("const YEAR: u16 = 2015;"));
+            assert!(main_rs.contains("const DAY: u8 = 25;"));
+            assert!(!main_rs.contains("{{"));
+            assert!(main_rs.contains("// TODO: add the example"));
+        }
+
+        let page = include_str!("../fixtures/puzzle_page.html");
+        let template = load_template(&templates_dir(), DEFAULT_TEMPLATE).unwrap();
+        let main_rs =
+            render_template(&template, &template_variables(2023, 4, Some(page), false)).unwrap();
+        assert!(main_rs.starts_with("// Advent of Code 2023 - Day 4: Lantern & Ladder\n"));
+        assert!(main_rs.contains(
+            "    // Example from the puzzle description
+    const TEST_INPUT: &str = \"3 lit
+5 dark
+<11> & more\";"
+        ));
+        assert!(main_rs.contains("assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 8);"));
+        assert!(main_rs.contains("assert_eq!(solve_b(&PuzzleInput::new(TEST_INPUT)), 5);"));
+
+        // A negative answer only fits into a template with a signed return type
+        let page = r#"<article class="day-desc"><pre><code>1 2</code></pre>
+<p>So the answer is <em><code>-3</code></em>.</p></article>"#;
+        for (name, signed) in [("default", false), ("grid", false), ("i64", true)] {
+            let template = load_template(&templates_dir(), name).unwrap();
+            assert_eq!(returns_signed(&template), signed);
+            let variables = template_variables(2023, 4, Some(page), signed);
+            let main_rs = render_template(&template, &variables).unwrap();
+            let assertion = "assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), -3);";
+            assert_eq!(main_rs.contains(assertion), signed, "{}", name);
+            assert_eq!(
+                main_rs.contains("the answer -3 of part A is negative"),
+                !signed,
+                "{}",
+                name
+            );
+        }
+
+        let error = load_template(&templates_dir(), "unknown").unwrap_err();
+        assert!(error.ends_with("default, grid, i64, sections"), "{}", error);
+    }
+
+    #[test]
+    fn test_crate_name() {
+        assert_eq!(crate_name(2023, 5), "day05");
+        assert_eq!(crate_name(2015, 25), "y2015d25");
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day(" 7\n"), Ok(7));
+        assert_eq!(parse_day("25"), Ok(25));
+        assert!(parse_day("-1").is_err());
+    }
 }
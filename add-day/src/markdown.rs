@@ -0,0 +1,309 @@
+// Converts the puzzle description of an advent of code day page to Markdown.
+// Only the handful of tags that appear in the descriptions are supported, everything else
+// is dropped and only its text is kept.
+
+const BASE_URL: &str = "https://adventofcode.com";
+const ARTICLE_START: &str = "<article class=\"day-desc\">";
+const ARTICLE_END: &str = "</article>";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Token<'a> {
+    Open(&'a str, &'a str),
+    Close(&'a str),
+    Text(String),
+}
+
+/// Splits the html into tags and decoded text. Comments and doctypes are skipped.
+pub fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..start])));
+        }
+
+        let tag = &rest[start..];
+        let Some(end) = tag.find('>') else {
+            // A lone '<' that doesn't start a tag
+            tokens.push(Token::Text(decode_entities(tag)));
+            break;
+        };
+        let inner = &tag[1..end];
+        rest = &tag[end + 1..];
+
+        if let Some(name) = inner.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim()));
+        } else if !inner.starts_with('!') {
+            let inner = inner.trim_end_matches('/').trim();
+            let (name, attributes) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+            tokens.push(Token::Open(name, attributes));
+        }
+    }
+
+    tokens
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let decoded = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((decoded, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Returns the value of the attribute with the given name from the attributes of a tag.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let start = attributes.find(&format!("{}=\"", name))? + name.len() + 2;
+    let length = attributes[start..].find('"')?;
+    Some(&attributes[start..start + length])
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with('/') {
+        format!("{}{}", BASE_URL, href)
+    } else {
+        href.to_string()
+    }
+}
+
+/// The contents of the puzzle descriptions in the page, one for every unlocked part.
+pub fn extract_articles(html: &str) -> Vec<&str> {
+    let mut articles = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find(ARTICLE_START) {
+        rest = &rest[start + ARTICLE_START.len()..];
+        let end = rest.find(ARTICLE_END).unwrap_or(rest.len());
+        articles.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    articles
+}
+
+/// The title of the puzzle without the dashes around it, e.g. `Day 1: Trebuchet?!`.
+pub fn extract_title(html: &str) -> Option<String> {
+    let article = extract_articles(html).into_iter().next()?;
+    let start = article.find("<h2>")? + "<h2>".len();
+    let end = start + article[start..].find("</h2>")?;
+    let title = decode_entities(&article[start..end]);
+    Some(title.trim().trim_matches('-').trim().to_string())
+}
+
+/// Converts a fragment of puzzle description html to Markdown.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut markdown = String::new();
+    let mut in_pre = false;
+    let mut code_depth = 0;
+    let mut links = Vec::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open("h2", _) => markdown.push_str("\n\n## "),
+            Token::Close("h2") => markdown.push_str("\n\n"),
+            Token::Open("p", _) => markdown.push_str("\n\n"),
+            Token::Close("p") => markdown.push_str("\n\n"),
+            Token::Open("ul", _) | Token::Close("ul") => markdown.push('\n'),
+            Token::Open("li", _) => markdown.push_str("\n- "),
+            Token::Open("pre", _) => {
+                markdown.push_str("\n\n```\n");
+                in_pre = true;
+            }
+            Token::Close("pre") => {
+                if !markdown.ends_with('\n') {
+                    markdown.push('\n');
+                }
+                markdown.push_str("```\n\n");
+                in_pre = false;
+            }
+            // Emphasis can't be shown inside of code in Markdown
+            Token::Open("em", _) | Token::Close("em") if !in_pre && code_depth == 0 => {
+                markdown.push('*')
+            }
+            Token::Open("code", _) if !in_pre => {
+                if code_depth == 0 {
+                    markdown.push('`');
+                }
+                code_depth += 1;
+            }
+            Token::Close("code") if !in_pre && code_depth > 0 => {
+                code_depth -= 1;
+                if code_depth == 0 {
+                    markdown.push('`');
+                }
+            }
+            Token::Open("a", attributes) => {
+                links.push(attribute(attributes, "href").map(absolute_url));
+                markdown.push('[');
+            }
+            Token::Close("a") => match links.pop().flatten() {
+                Some(href) => markdown.push_str(&format!("]({})", href)),
+                None => markdown.push(']'),
+            },
+            Token::Text(text) if in_pre => markdown.push_str(&text),
+            Token::Text(text) => {
+                // Whitespace is collapsed like a browser would do it
+                let mut last_was_space = markdown.ends_with([' ', '\n']);
+                for c in text.chars() {
+                    if c.is_whitespace() {
+                        if !last_was_space {
+                            markdown.push(' ');
+                        }
+                        last_was_space = true;
+                    } else {
+                        markdown.push(c);
+                        last_was_space = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tidy(&markdown)
+}
+
+/// Removes the spaces around lines and the dashes around headings outside of code blocks,
+/// and collapses runs of empty lines.
+fn tidy(markdown: &str) -> String {
+    let mut result = String::new();
+    let mut in_block = false;
+    let mut empty_lines = 0;
+
+    for line in markdown.trim().lines() {
+        let heading;
+        let line = match line.trim().strip_prefix("## ") {
+            _ if in_block => line,
+            Some(title) => {
+                heading = format!("## {}", title.trim_matches('-').trim());
+                &heading
+            }
+            None => line.trim(),
+        };
+        if line.starts_with("```") {
+            in_block = !in_block;
+        }
+
+        if line.is_empty() && !in_block {
+            empty_lines += 1;
+            continue;
+        }
+        if empty_lines > 0 && !result.is_empty() {
+            result.push('\n');
+        }
+        empty_lines = 0;
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Converts every unlocked part of the puzzle page to a single Markdown document.
+pub fn puzzle_to_markdown(html: &str) -> String {
+    extract_articles(html)
+        .into_iter()
+        .map(html_to_markdown)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = include_str!("../fixtures/puzzle_page.html");
+
+    #[test]
+    fn test_extract_articles() {
+        let articles = extract_articles(PAGE);
+        assert_eq!(articles.len(), 2);
+        assert!(articles[0].starts_with("<h2>--- Day 4: Lantern &amp; Ladder ---</h2>"));
+        assert!(articles[1].starts_with("<h2 id=\"part2\">--- Part Two ---</h2>"));
+        assert_eq!(
+            extract_title(PAGE),
+            Some("Day 4: Lantern & Ladder".to_string())
+        );
+        assert_eq!(extract_title("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_puzzle_to_markdown() {
+        let expected = "## Day 4: Lantern & Ladder
+
+The elves hang *lanterns* on a [ladder](https://adventofcode.com/2023/about) that leans against the `<wall>`.
+
+For example:
+
+```
+3 lit
+5 dark
+<11> & more
+```
+
+The ladder above has `8` lanterns and a [hint](https://example.com/hint).
+
+- A *lit* lantern counts `1`.
+- A dark lantern counts `0`.
+
+What is the total of the lanterns?
+
+## Part Two
+
+Now count the dark ones instead, which is `5` in the example.
+";
+        assert_eq!(puzzle_to_markdown(PAGE), expected);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#39;c&#x27; &amp;amp;"),
+            "a <b> 'c' &amp;"
+        );
+        assert_eq!(
+            decode_entities("fish & chips &unknown;"),
+            "fish & chips &unknown;"
+        );
+    }
+}
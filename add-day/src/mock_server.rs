@@ -0,0 +1,210 @@
+// A small stand-in for the advent of code website, so the network paths of add-day can be
+// tested without network access. It answers like the real site for a fixed session:
+// days from LOCKED_FROM on are not unlocked yet, other sessions are rejected for inputs and
+// answers and only see part one of the puzzle page. Every request for SERVER_ERROR_YEAR fails
+// like an overloaded server. The event page of a year shows USER_NAME for the session.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const SESSION: &str = "test-session";
+pub const LOCKED_FROM: u8 = 20;
+pub const SERVER_ERROR_YEAR: u16 = 2016;
+pub const USER_NAME: &str = "Test User";
+
+/// Answers with a fixed verdict, every other answer is wrong without a hint.
+pub const CORRECT_ANSWER: &str = "42";
+pub const TOO_LOW_ANSWER: &str = "1";
+pub const TOO_HIGH_ANSWER: &str = "1000";
+pub const RATE_LIMITED_ANSWER: &str = "7";
+pub const SOLVED_ANSWER: &str = "0";
+
+const PUZZLE_PAGE: &str = include_str!("../fixtures/puzzle_page.html");
+const PART_TWO_START: &str = "<article class=\"day-desc\"><h2 id=\"part2\">";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub cookie: Option<String>,
+    pub user_agent: Option<String>,
+    pub body: String,
+}
+
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Starts the server on a free port. It runs until the tests are done.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle(stream, &recorded);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let mut cookie = None;
+    let mut user_agent = None;
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':')?;
+        match name.to_ascii_lowercase().as_str() {
+            "cookie" => cookie = Some(value.trim().to_string()),
+            "user-agent" => user_agent = Some(value.trim().to_string()),
+            "content-length" => content_length = value.trim().parse().ok()?,
+            _ => {}
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(Request {
+        method,
+        path,
+        cookie,
+        user_agent,
+        body: String::from_utf8(body).ok()?,
+    })
+}
+
+/// Answers the request. It is recorded before the response is written, so a client that got
+/// the response always finds its request in [`MockServer::requests`].
+fn handle(mut stream: TcpStream, recorded: &Mutex<Vec<Request>>) -> Option<()> {
+    let request = read_request(&stream)?;
+    let (status, body) = respond(&request);
+    recorded.lock().unwrap().push(request);
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        500 => "Internal Server Error",
+        _ => "Not Found",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+    .ok()
+}
+
+fn article(text: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+        text
+    )
+}
+
+/// Parses `/<year>/day/<day>` with an optional suffix like `/input`.
+fn parse_path(path: &str) -> Option<(u16, u8, &str)> {
+    let mut parts = path.trim_start_matches('/').splitn(4, '/');
+    let year = parts.next()?.parse().ok()?;
+    if parts.next()? != "day" {
+        return None;
+    }
+    let day = parts.next()?.parse().ok()?;
+    Some((year, day, parts.next().unwrap_or("")))
+}
+
+fn respond(request: &Request) -> (u16, String) {
+    let not_found = (404, "404 Not Found".to_string());
+    let logged_in = request.cookie.as_deref() == Some(&format!("session={}", SESSION));
+    if request.method == "GET" && request.path.trim_start_matches('/').parse::<u16>().is_ok() {
+        let header = if logged_in {
+            format!(
+                "<div class=\"user\">{} <span class=\"star-count\">8*</span></div>",
+                USER_NAME
+            )
+        } else {
+            "<a href=\"/auth/login\">[Log In]</a>".to_string()
+        };
+        return (
+            200,
+            format!(
+                "<!DOCTYPE html>\n<html><body><header>{}</header></body></html>",
+                header
+            ),
+        );
+    }
+    let Some((year, day, endpoint)) = parse_path(&request.path) else {
+        return not_found;
+    };
+    if year == SERVER_ERROR_YEAR {
+        return (500, "Internal Server Error".to_string());
+    }
+    if day >= LOCKED_FROM {
+        return (
+            404,
+            "Please don't repeatedly request this endpoint before it unlocks! The calendar countdown is synchronized with the server time; the link will be enabled on the calendar the instant this puzzle becomes available.\n".to_string(),
+        );
+    }
+
+    match (request.method.as_str(), endpoint) {
+        ("GET", "input") if logged_in => (200, format!("input of {} day {}\n", year, day)),
+        ("GET", "input") => (
+            400,
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n".to_string(),
+        ),
+        ("GET", "") if logged_in => (200, PUZZLE_PAGE.to_string()),
+        ("GET", "") => {
+            let end = PUZZLE_PAGE.find(PART_TWO_START).unwrap();
+            (200, format!("{}</main></body></html>", &PUZZLE_PAGE[..end]))
+        }
+        ("POST", "answer") if logged_in => {
+            let answer = request
+                .body
+                .split('&')
+                .find_map(|field| field.strip_prefix("answer="))
+                .unwrap_or_default();
+            let text = match answer {
+                CORRECT_ANSWER => "That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer.",
+                TOO_LOW_ANSWER => "That's not the right answer; your answer is too low.  Please wait one minute before trying again.",
+                TOO_HIGH_ANSWER => "That's not the right answer; your answer is too high.  Please wait one minute before trying again.",
+                RATE_LIMITED_ANSWER => "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 3s left to wait.",
+                SOLVED_ANSWER => "You don't seem to be solving the right level.  Did you already complete it?",
+                _ => "That's not the right answer.  If you're stuck, make sure you're using the full input data.",
+            };
+            (200, article(text))
+        }
+        ("POST", "answer") => (400, "Bad Request".to_string()),
+        _ => not_found,
+    }
+}
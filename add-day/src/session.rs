@@ -0,0 +1,408 @@
+// Manages the session cookie outside of the repository. `add-day session set` stores the token
+// in $XDG_CONFIG_HOME/aoc/session (or ~/.config/aoc/session), readable only by the user, and
+// the client falls back to it when AOC_SESSION is not set. The token is validated against the
+// event page, which shows the name of the logged in user. Sessions expire after about a month,
+// so the time it was stored is kept next to the token to warn before that happens.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::client::{Client, FetchError};
+use crate::markdown::{tokenize, Token};
+use crate::year_from_env;
+
+pub const USAGE: &str = "Usage: add-day session set [<token>]
+       add-day session check
+
+set    Validates the session token and stores it in the config file, which is used
+       when AOC_SESSION is not set. Without a token, it is read from stdin.
+check  Validates the current session and shows the logged in user.
+
+The config file is $XDG_CONFIG_HOME/aoc/session, or ~/.config/aoc/session.";
+
+const SESSION_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// How long before the expiry to start warning.
+const EXPIRY_WARNING: Duration = Duration::from_secs(5 * 24 * 60 * 60);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionOptions {
+    Set(Option<String>),
+    Check,
+}
+
+impl SessionOptions {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let options = match args.next().as_deref() {
+            Some("set") => SessionOptions::Set(args.next()),
+            Some("check") => SessionOptions::Check,
+            Some("-h" | "--help") | None => return Err(USAGE.to_string()),
+            Some(arg) => return Err(format!("Unknown session command: {}\n\n{}", arg, USAGE)),
+        };
+        match args.next() {
+            Some(arg) => Err(format!("Unexpected argument: {}", arg)),
+            None => Ok(options),
+        }
+    }
+}
+
+/// The session as it is kept in the config file: the token on the first line and the
+/// seconds since the unix epoch it was stored at on the second.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StoredSession {
+    pub token: String,
+    pub stored_at: Option<SystemTime>,
+}
+
+impl StoredSession {
+    fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        let token = lines.next()?.trim();
+        if token.is_empty() {
+            return None;
+        }
+        let stored_at = lines
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds));
+        Some(Self {
+            token: token.to_string(),
+            stored_at,
+        })
+    }
+
+    /// The warning from [`expiry_warning`], if the client uses this session.
+    fn expiry_warning(&self, client: &Client, now: SystemTime) -> Option<String> {
+        if !client.has_session_token(&self.token) {
+            return None;
+        }
+        expiry_warning(self.stored_at?, now)
+    }
+
+    /// Reads the session, if there is one at `path`.
+    pub fn load(path: &Path) -> Option<Self> {
+        Self::parse(&fs::read_to_string(path).ok()?)
+    }
+
+    /// Writes the session to `path`, which only the user can read.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut content = format!("{}\n", self.token);
+        if let Some(stored_at) = self.stored_at {
+            let seconds = stored_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+            content += &format!("{}\n", seconds.as_secs());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Could not create {}: {}", parent.display(), e))?;
+        }
+        write_private(path, &content)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> io::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // The mode only applies to new files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> io::Result<()> {
+    fs::write(path, content)
+}
+
+/// Where the session is stored, None if neither XDG_CONFIG_HOME nor HOME is set.
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("aoc").join("session"))
+}
+
+/// The token from the config file.
+pub fn load_stored() -> Option<String> {
+    Some(StoredSession::load(&config_path()?)?.token)
+}
+
+/// Prints the expiry warning of the stored session if the client uses it. The session
+/// command doesn't call this, `session check` warns itself and `session set` replaces it.
+pub fn warn_if_expiring(client: &Client, now: SystemTime) {
+    if let Some(warning) = config_path()
+        .and_then(|path| StoredSession::load(&path))
+        .and_then(|stored| stored.expiry_warning(client, now))
+    {
+        println!("{}", warning);
+    }
+}
+
+/// A warning if a session stored at `stored_at` is close to its expiry or past it.
+pub fn expiry_warning(stored_at: SystemTime, now: SystemTime) -> Option<String> {
+    let age = now.duration_since(stored_at).unwrap_or_default();
+    let days = age.as_secs() / (24 * 60 * 60);
+    if age >= SESSION_LIFETIME {
+        Some(format!(
+            "The session was stored {} days ago and has probably expired, log in again and run add-day session set",
+            days
+        ))
+    } else if age + EXPIRY_WARNING >= SESSION_LIFETIME {
+        Some(format!(
+            "The session was stored {} days ago and expires after about {} days, log in again soon and run add-day session set",
+            days,
+            SESSION_LIFETIME.as_secs() / (24 * 60 * 60)
+        ))
+    } else {
+        None
+    }
+}
+
+/// Accepts the token with or without the `session=` of the cookie.
+fn parse_token(token: &str) -> Result<String, String> {
+    let token = token.trim();
+    let token = token.strip_prefix("session=").unwrap_or(token);
+    if token.is_empty() || token.contains(char::is_whitespace) || token.contains(';') {
+        return Err(format!("Invalid session token {:?}", token));
+    }
+    Ok(token.to_string())
+}
+
+/// The name in `<div class="user">` of the event page, which is only there when logged in.
+fn parse_user(html: &str) -> Option<String> {
+    let mut tokens = tokenize(html).into_iter();
+    tokens.find(
+        |token| matches!(token, Token::Open("div", attrs) if attrs.contains("class=\"user\"")),
+    )?;
+    match tokens.next()? {
+        Token::Text(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the user the session of the client belongs to.
+fn validate(client: &Client, year: u16) -> Result<String, String> {
+    let html = client.fetch_event(year).map_err(|e| e.to_string())?;
+    parse_user(&html).ok_or(FetchError::ExpiredSession.to_string())
+}
+
+fn read_token() -> Result<String, String> {
+    print!("Please paste the value of the session cookie: ");
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    let mut token = String::new();
+    io::stdin()
+        .read_line(&mut token)
+        .map_err(|e| format!("Failed to read the session: {}", e))?;
+    Ok(token)
+}
+
+/// Runs the session command with the session stored at `path`.
+pub fn run_session(
+    options: &SessionOptions,
+    path: Option<&Path>,
+    client: Client,
+    now: SystemTime,
+) -> Result<(), String> {
+    let path = path.ok_or("Neither XDG_CONFIG_HOME nor HOME is set")?;
+    let year = year_from_env()?;
+
+    match options {
+        SessionOptions::Set(token) => {
+            let token = match token {
+                Some(token) => parse_token(token)?,
+                None => parse_token(&read_token()?)?,
+            };
+            // Only a working session is stored
+            let user = validate(&client.with_session(token.clone()), year)?;
+            StoredSession {
+                token,
+                stored_at: Some(now),
+            }
+            .save(path)?;
+            println!(
+                "Logged in as {}, stored the session in {}",
+                user,
+                path.display()
+            );
+        }
+        SessionOptions::Check => {
+            let user = validate(&client, year)?;
+            println!("Logged in as {}", user);
+            match StoredSession::load(path) {
+                Some(stored) if client.has_session_token(&stored.token) => {
+                    if let Some(warning) = stored.expiry_warning(&client, now) {
+                        println!("{}", warning);
+                    }
+                }
+                _ => println!(
+                    "The session is not from {}, its age is unknown",
+                    path.display()
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockServer, SESSION, USER_NAME};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("add-day-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("aoc").join("session")
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parse = |args: &[&str]| SessionOptions::parse(args.iter().map(|s| s.to_string()));
+        assert_eq!(
+            parse(&["set", "abc"]),
+            Ok(SessionOptions::Set(Some("abc".to_string())))
+        );
+        assert_eq!(parse(&["set"]), Ok(SessionOptions::Set(None)));
+        assert_eq!(parse(&["check"]), Ok(SessionOptions::Check));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["get"]).is_err());
+        assert!(parse(&["check", "abc"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_token() {
+        assert_eq!(parse_token("abc123\n"), Ok("abc123".to_string()));
+        assert_eq!(parse_token("session=abc123"), Ok("abc123".to_string()));
+        assert!(parse_token("").is_err());
+        assert!(parse_token("abc 123").is_err());
+    }
+
+    #[test]
+    fn test_parse_user() {
+        let html = "<header><div class=\"user\">Jane Doe <span class=\"star-count\">8*</span></div></header>";
+        assert_eq!(parse_user(html), Some("Jane Doe".to_string()));
+        assert_eq!(
+            parse_user("<header><a href=\"/auth/login\">[Log In]</a></header>"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = test_path("session-store");
+        let session = StoredSession {
+            token: "abc123".to_string(),
+            stored_at: Some(UNIX_EPOCH + Duration::from_secs(1700000000)),
+        };
+        session.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "abc123\n1700000000\n");
+        assert_eq!(StoredSession::load(&path), Some(session));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Written by hand without the time
+        fs::write(&path, "abc123\n").unwrap();
+        assert_eq!(StoredSession::load(&path).unwrap().stored_at, None);
+        fs::write(&path, "\n").unwrap();
+        assert_eq!(StoredSession::load(&path), None);
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_expiry_warning() {
+        let stored_at = UNIX_EPOCH + Duration::from_secs(1700000000);
+        assert_eq!(expiry_warning(stored_at, stored_at), None);
+        assert_eq!(expiry_warning(stored_at, stored_at + 24 * DAY), None);
+        assert!(expiry_warning(stored_at, stored_at + 26 * DAY)
+            .unwrap()
+            .contains("stored 26 days ago and expires after about 30 days"));
+        assert!(expiry_warning(stored_at, stored_at + 31 * DAY)
+            .unwrap()
+            .contains("has probably expired"));
+    }
+
+    #[test]
+    fn test_stored_expiry_warning() {
+        let stored_at = UNIX_EPOCH + Duration::from_secs(1700000000);
+        let stored = StoredSession {
+            token: SESSION.to_string(),
+            stored_at: Some(stored_at),
+        };
+        let client = Client::new("http://localhost", Some(SESSION.to_string()));
+        assert!(stored
+            .expiry_warning(&client, stored_at + 27 * DAY)
+            .is_some());
+        assert_eq!(stored.expiry_warning(&client, stored_at), None);
+        let unknown_age = StoredSession {
+            token: SESSION.to_string(),
+            stored_at: None,
+        };
+        assert_eq!(
+            unknown_age.expiry_warning(&client, stored_at + 27 * DAY),
+            None
+        );
+
+        // Only the session that is used matters
+        let client = Client::new("http://localhost", Some("other".to_string()));
+        assert_eq!(stored.expiry_warning(&client, stored_at + 27 * DAY), None);
+    }
+
+    #[test]
+    fn test_run_session() {
+        let server = MockServer::start();
+        let path = test_path("session-run");
+        let now = UNIX_EPOCH + Duration::from_secs(1700000000);
+
+        // A rejected token is not stored
+        let client = Client::new(&server.url(), None);
+        let set = |token: &str| SessionOptions::Set(Some(token.to_string()));
+        let error = run_session(&set("expired"), Some(&path), client, now).unwrap_err();
+        assert_eq!(error, FetchError::ExpiredSession.to_string());
+        assert!(!path.exists());
+
+        let client = Client::new(&server.url(), None);
+        run_session(&set(SESSION), Some(&path), client, now).unwrap();
+        assert_eq!(
+            StoredSession::load(&path),
+            Some(StoredSession {
+                token: SESSION.to_string(),
+                stored_at: Some(now)
+            })
+        );
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "/2023");
+        assert_eq!(request.cookie, Some(format!("session={}", SESSION)));
+
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        run_session(&SessionOptions::Check, Some(&path), client, now + 27 * DAY).unwrap();
+        let client = Client::new(&server.url(), Some("expired".to_string()));
+        assert!(run_session(&SessionOptions::Check, Some(&path), client, now).is_err());
+        assert!(run_session(
+            &SessionOptions::Check,
+            None,
+            Client::new(&server.url(), None),
+            now
+        )
+        .is_err());
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        assert_eq!(validate(&client, 2023), Ok(USER_NAME.to_string()));
+        fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+}
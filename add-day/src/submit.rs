@@ -0,0 +1,371 @@
+// Submits an answer of a day and records the verdict in the answer ledger of the day, so an
+// answer that is already known to be wrong is never submitted again. Answers that can't be
+// right because of earlier too high or too low guesses are only submitted with --force.
+
+use std::path::Path;
+use std::time::Duration;
+
+use aoc_utils::day_directory;
+use aoc_utils::ledger::{Check, Ledger, Part, Submission, Verdict};
+
+use crate::client::Client;
+use crate::markdown::{tokenize, Token};
+use crate::{parse_day, parse_year, year_from_env};
+
+pub const USAGE: &str = "Usage: add-day submit <day> <part> <answer> [--year <year>] [--force]
+
+Submits the answer for part a or b of the given day and records the verdict in
+the answers_dayNN.txt of the day. Answers that were already rejected are not submitted again.
+
+Options:
+    --year <year>  Year of the puzzle (default: AOC_YEAR from the environment or .env, else 2023)
+    --force        Submit even if earlier too high or too low guesses rule the answer out";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SubmitOptions {
+    day: u8,
+    part: Part,
+    answer: String,
+    year: Option<u16>,
+    force: bool,
+}
+
+impl SubmitOptions {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut year = None;
+        let mut force = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--year" => {
+                    let value = args.next().ok_or("Missing value for --year")?;
+                    year = Some(parse_year(&value)?);
+                }
+                "--force" => force = true,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+                _ => positional.push(arg),
+            }
+        }
+
+        let [day, part, answer] = <[String; 3]>::try_from(positional)
+            .map_err(|_| format!("Expected a day, a part and an answer\n\n{}", USAGE))?;
+        let part = Part::parse(&part).ok_or(format!("Invalid part {}, has to be a or b", part))?;
+        if answer.is_empty() || answer.contains(char::is_whitespace) {
+            return Err(format!("Invalid answer {:?}", answer));
+        }
+
+        Ok(Self {
+            day: parse_day(&day)?,
+            part,
+            answer,
+            year,
+            force,
+        })
+    }
+}
+
+/// The outcome of a submission according to the response page.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Response {
+    Verdict(Verdict),
+    AlreadySolved,
+    /// The answer was not checked, because the last one was submitted too recently.
+    RateLimited(Option<Duration>),
+    Unknown(String),
+}
+
+/// The text of the main article of the page without any tags.
+fn article_text(html: &str) -> String {
+    let start = html.find("<article").unwrap_or(0);
+    let end = html[start..]
+        .find("</article>")
+        .map_or(html.len(), |end| start + end);
+
+    let text = tokenize(&html[start..end])
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect::<String>();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses a wait time like `1m 3s` from `You have 1m 3s left to wait.`
+fn parse_wait(text: &str) -> Option<Duration> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+
+    let mut seconds = 0;
+    for amount in text[start..end].split_whitespace() {
+        let unit = match amount.chars().last()? {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds += amount[..amount.len() - 1].parse::<u64>().ok()? * unit;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+pub fn parse_response(html: &str) -> Response {
+    let text = article_text(html);
+
+    if text.contains("That's the right answer") {
+        Response::Verdict(Verdict::Correct)
+    } else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Response::Verdict(Verdict::TooHigh)
+        } else if text.contains("your answer is too low") {
+            Response::Verdict(Verdict::TooLow)
+        } else {
+            Response::Verdict(Verdict::Wrong)
+        }
+    } else if text.contains("You gave an answer too recently") {
+        Response::RateLimited(parse_wait(&text))
+    } else if text.contains("Did you already complete it") {
+        Response::AlreadySolved
+    } else {
+        Response::Unknown(text)
+    }
+}
+
+/// Fails if the ledger already knows the verdict for the answer or the part is solved. An
+/// answer outside of the bounds of earlier guesses is only submitted with `force`.
+fn check_ledger(ledger: &Ledger, part: Part, answer: &str, force: bool) -> Result<(), String> {
+    match ledger.check(part, answer) {
+        Check::Unknown => Ok(()),
+        Check::Accepted => Err(format!("{} was already accepted for part {}", answer, part)),
+        check @ (Check::Mismatch(_) | Check::Rejected(_)) => Err(format!(
+            "Not submitting {} for part {}, {}",
+            answer, part, check
+        )),
+        check if force => {
+            println!("Warning: your answer {} is {}", answer, check);
+            Ok(())
+        }
+        check => Err(format!(
+            "Your answer {} is {}, use --force to submit it anyway",
+            answer, check
+        )),
+    }
+}
+
+/// Submits the answer for the day inside of the workspace at `root`.
+pub fn run_submit(options: &SubmitOptions, root: &Path, client: &Client) -> Result<(), String> {
+    let year = match options.year {
+        Some(year) => year,
+        None => year_from_env()?,
+    };
+    let day_dir = root.join(day_directory(year, options.day));
+    if !day_dir.is_dir() {
+        return Err(format!("{} does not exist", day_dir.display()));
+    }
+
+    let mut ledger =
+        Ledger::load(day_dir.join(Ledger::file_name(options.day))).map_err(|e| e.to_string())?;
+    check_ledger(&ledger, options.part, &options.answer, options.force)?;
+
+    let html = client
+        .post_answer(year, options.day, options.part, &options.answer)
+        .map_err(|e| e.to_string())?;
+    match parse_response(&html) {
+        Response::Verdict(verdict) => {
+            ledger
+                .record(Submission {
+                    part: options.part,
+                    answer: options.answer.clone(),
+                    verdict,
+                })
+                .map_err(|e| e.to_string())?;
+            match verdict {
+                Verdict::Correct => println!("That's the right answer!"),
+                Verdict::TooHigh => println!("That's not the right answer, it is too high"),
+                Verdict::TooLow => println!("That's not the right answer, it is too low"),
+                Verdict::Wrong => println!("That's not the right answer"),
+            }
+            Ok(())
+        }
+        Response::AlreadySolved => {
+            println!("Part {} is already solved", options.part);
+            Ok(())
+        }
+        Response::RateLimited(Some(wait)) => Err(format!(
+            "Answered too recently, try again in {}s",
+            wait.as_secs()
+        )),
+        Response::RateLimited(None) => Err("Answered too recently, try again later".to_string()),
+        Response::Unknown(text) => Err(format!("Unexpected response: {}", text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::FetchError;
+    use crate::mock_server::{
+        MockServer, CORRECT_ANSWER, RATE_LIMITED_ANSWER, SESSION, SOLVED_ANSWER, TOO_HIGH_ANSWER,
+        TOO_LOW_ANSWER,
+    };
+
+    fn page(article: &str) -> String {
+        format!(
+            "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+            article
+        )
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(&page(
+                "That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer."
+            )),
+            Response::Verdict(Verdict::Correct)
+        );
+        assert_eq!(
+            parse_response(&page(
+                "That's not the right answer; your answer is too high.  Please wait one minute."
+            )),
+            Response::Verdict(Verdict::TooHigh)
+        );
+        assert_eq!(
+            parse_response(&page(
+                "That's not the right answer; your answer is too low."
+            )),
+            Response::Verdict(Verdict::TooLow)
+        );
+        assert_eq!(
+            parse_response(&page(
+                "That's not the right answer.  If you're stuck, <a href=\"/help\">ask</a>."
+            )),
+            Response::Verdict(Verdict::Wrong)
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            )),
+            Response::AlreadySolved
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 3s left to wait."
+            )),
+            Response::RateLimited(Some(Duration::from_secs(63)))
+        );
+        assert_eq!(
+            parse_response("<html>Maintenance</html>"),
+            Response::Unknown("Maintenance".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_ledger() {
+        let path = std::env::temp_dir().join(format!("submit-ledger-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut ledger = Ledger::load(&path).unwrap();
+        assert_eq!(check_ledger(&ledger, Part::A, "10", false), Ok(()));
+
+        ledger
+            .record(Submission {
+                part: Part::A,
+                answer: "10".to_string(),
+                verdict: Verdict::TooLow,
+            })
+            .unwrap();
+        assert!(check_ledger(&ledger, Part::A, "10", true).is_err());
+        assert_eq!(check_ledger(&ledger, Part::A, "12", false), Ok(()));
+        assert_eq!(check_ledger(&ledger, Part::B, "10", false), Ok(()));
+        // Below the too low guess
+        assert_eq!(
+            check_ledger(&ledger, Part::A, "9", false),
+            Err("Your answer 9 is not above the previously rejected too-low guess 10, use --force to submit it anyway".to_string())
+        );
+        assert_eq!(check_ledger(&ledger, Part::A, "9", true), Ok(()));
+
+        ledger
+            .record(Submission {
+                part: Part::A,
+                answer: "12".to_string(),
+                verdict: Verdict::Correct,
+            })
+            .unwrap();
+        assert!(check_ledger(&ledger, Part::A, "12", true).is_err());
+        assert!(check_ledger(&ledger, Part::A, "13", true).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_submit_with_mock_server() {
+        let server = MockServer::start();
+        let client = Client::new(&server.url(), Some(SESSION.to_string()));
+        let root = std::env::temp_dir().join(format!("submit-mock-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("day04")).unwrap();
+        let submit = |part: Part, answer: &str| {
+            let options = SubmitOptions {
+                day: 4,
+                part,
+                answer: answer.to_string(),
+                year: Some(2023),
+                force: false,
+            };
+            run_submit(&options, &root, &client)
+        };
+
+        assert!(submit(Part::A, RATE_LIMITED_ANSWER)
+            .unwrap_err()
+            .contains("63s"));
+        assert_eq!(submit(Part::A, TOO_LOW_ANSWER), Ok(()));
+        assert_eq!(submit(Part::A, "5"), Ok(()));
+        assert_eq!(submit(Part::A, TOO_HIGH_ANSWER), Ok(()));
+        assert_eq!(submit(Part::A, CORRECT_ANSWER), Ok(()));
+        assert_eq!(submit(Part::B, SOLVED_ANSWER), Ok(()));
+        // Known answers are not sent again
+        assert!(submit(Part::A, "5").is_err());
+        assert_eq!(server.requests().len(), 6);
+
+        let ledger = Ledger::load(root.join("day04").join(Ledger::file_name(4))).unwrap();
+        assert_eq!(
+            ledger
+                .submissions()
+                .iter()
+                .map(|submission| submission.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "A 1 too-low",
+                "A 5 wrong",
+                "A 1000 too-high",
+                "A 42 correct"
+            ]
+        );
+
+        let client = Client::new(&server.url(), Some("expired".to_string()));
+        let options = SubmitOptions::parse(["4", "b", "3"].map(String::from).into_iter()).unwrap();
+        let error = run_submit(&options, &root, &client).unwrap_err();
+        assert_eq!(error, FetchError::ExpiredSession.to_string());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_submit_options() {
+        let parse = |args: &[&str]| SubmitOptions::parse(args.iter().map(|s| s.to_string()));
+        assert_eq!(
+            parse(&["3", "b", "-12", "--year", "2022", "--force"]),
+            Ok(SubmitOptions {
+                day: 3,
+                part: Part::B,
+                answer: "-12".to_string(),
+                year: Some(2022),
+                force: true,
+            })
+        );
+        assert!(parse(&["3", "c", "12"]).is_err());
+        assert!(parse(&["3", "a"]).is_err());
+        assert!(parse(&["3", "a", "1", "2"]).is_err());
+        assert!(parse(&["3", "a", ""]).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+// Waiting for a puzzle to unlock, for the --wait option. Puzzles unlock at midnight EST (UTC-5)
+// on the day of December. The clock is behind the Clock trait, so the tests don't have to wait.
+// Because the clocks of this machine and the server may differ a bit, requests that still fail
+// with 404 after the unlock are retried with a growing delay.
+
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::client::FetchError;
+
+/// The offset of EST to UTC in hours.
+const UNLOCK_UTC_OFFSET: i64 = -5;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRIES: usize = 8;
+
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Days since 1970-01-01 for a date of the proleptic gregorian calendar,
+/// see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The instant the puzzle of the given day unlocks.
+pub fn unlock_time(year: u16, day: u8) -> SystemTime {
+    let midnight = days_from_civil(year as i64, 12, day as i64) * SECONDS_PER_DAY;
+    let seconds = midnight - UNLOCK_UTC_OFFSET * 60 * 60;
+    UNIX_EPOCH + Duration::from_secs(seconds as u64)
+}
+
+/// Formats the remaining time like `2d 03h 04m 05s`, leaving out days and hours if there are none.
+pub fn format_countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    let (days, hours) = (seconds / 86400, seconds / 3600 % 24);
+    let (minutes, seconds) = (seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {:02}h {:02}m {:02}s", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+/// Shows a countdown until the puzzle unlocks. Returns right away if it already is.
+pub fn wait_for_unlock(clock: &dyn Clock, year: u16, day: u8) {
+    let unlock = unlock_time(year, day);
+    let mut waited = false;
+    while let Ok(remaining) = unlock.duration_since(clock.now()) {
+        if remaining.is_zero() {
+            break;
+        }
+        print!(
+            "\rDay {} of {} unlocks in {}   ",
+            day,
+            year,
+            format_countdown(remaining)
+        );
+        io::stdout().flush().ok();
+        // Sleeping to the next full second keeps the countdown in step with the clock
+        let sub_second = Duration::from_nanos(remaining.subsec_nanos() as u64);
+        clock.sleep(if sub_second.is_zero() {
+            Duration::from_secs(1)
+        } else {
+            sub_second
+        });
+        waited = true;
+    }
+    if waited {
+        println!("\rDay {} of {} is unlocked!{}", day, year, " ".repeat(20));
+    }
+}
+
+/// Calls `request` until the puzzle is no longer reported as not unlocked, doubling the delay
+/// between the attempts. Gives up with [`FetchError::NotUnlocked`] after [`MAX_RETRIES`] retries.
+pub fn retry_until_unlocked<T>(
+    clock: &dyn Clock,
+    mut request: impl FnMut() -> Result<T, FetchError>,
+) -> Result<T, FetchError> {
+    let mut delay = FIRST_RETRY_DELAY;
+    for _ in 0..MAX_RETRIES {
+        match request() {
+            Err(FetchError::NotUnlocked) => {
+                println!("Not unlocked yet, trying again in {}s", delay.as_secs());
+                clock.sleep(delay);
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+    request()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// A clock that only moves when something sleeps.
+    pub struct FakeClock {
+        now: Cell<SystemTime>,
+        pub sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl FakeClock {
+        pub fn new(now: SystemTime) -> Self {
+            Self {
+                now: Cell::new(now),
+                sleeps: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> SystemTime {
+            self.now.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+
+    fn seconds_since_epoch(time: SystemTime) -> u64 {
+        time.duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn test_unlock_time() {
+        // 2023-12-01T05:00:00Z
+        assert_eq!(seconds_since_epoch(unlock_time(2023, 1)), 1701406800);
+        // 2024-12-25T05:00:00Z, after a leap day
+        assert_eq!(seconds_since_epoch(unlock_time(2024, 25)), 1735102800);
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(Duration::from_secs(5)), "0m 05s");
+        assert_eq!(format_countdown(Duration::from_secs(3723)), "1h 02m 03s");
+        assert_eq!(
+            format_countdown(Duration::from_secs(2 * 86400 + 5)),
+            "2d 00h 00m 05s"
+        );
+    }
+
+    #[test]
+    fn test_wait_for_unlock() {
+        let unlock = unlock_time(2023, 3);
+        let clock = FakeClock::new(unlock - Duration::from_millis(2500));
+        wait_for_unlock(&clock, 2023, 3);
+        assert_eq!(clock.now(), unlock);
+        assert_eq!(
+            *clock.sleeps.borrow(),
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(1)
+            ]
+        );
+
+        // Already unlocked
+        let clock = FakeClock::new(unlock + Duration::from_secs(1));
+        wait_for_unlock(&clock, 2023, 3);
+        assert!(clock.sleeps.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_retry_until_unlocked() {
+        let clock = FakeClock::new(UNIX_EPOCH);
+        let mut attempts = 0;
+        let result = retry_until_unlocked(&clock, || {
+            attempts += 1;
+            if attempts < 4 {
+                Err(FetchError::NotUnlocked)
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result, Ok(4));
+        assert_eq!(
+            *clock.sleeps.borrow(),
+            [1, 2, 4].map(Duration::from_secs).to_vec()
+        );
+
+        // Other errors are not retried
+        let clock = FakeClock::new(UNIX_EPOCH);
+        let result: Result<(), _> = retry_until_unlocked(&clock, || Err(FetchError::NoSession));
+        assert_eq!(result, Err(FetchError::NoSession));
+        assert!(clock.sleeps.borrow().is_empty());
+
+        // Gives up eventually, without waiting longer than the maximum delay
+        let result: Result<(), _> = retry_until_unlocked(&clock, || Err(FetchError::NotUnlocked));
+        assert_eq!(result, Err(FetchError::NotUnlocked));
+        assert_eq!(clock.sleeps.borrow().len(), MAX_RETRIES);
+        assert_eq!(clock.sleeps.borrow().last(), Some(&MAX_RETRY_DELAY));
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// A set of small integers in the range `0..128`, stored as the bits of a single `u128`.
+///
+/// Useful for puzzles whose values come from a small bounded domain, because
+/// membership tests, intersections and counting are all single instructions.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BitSet128(u128);
+
+impl BitSet128 {
+    /// The number of distinct values that can be stored (`0..CAPACITY`).
+    pub const CAPACITY: usize = 128;
+
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    /// Adds the value to the set and returns whether it was newly inserted.
+    /// Panics if the value is not below [`Self::CAPACITY`].
+    pub fn insert(&mut self, value: usize) -> bool {
+        assert!(
+            value < Self::CAPACITY,
+            "Value {} does not fit into a BitSet128",
+            value
+        );
+        let was_present = self.contains(value);
+        self.0 |= 1 << value;
+        !was_present
+    }
+
+    /// Removes the value from the set and returns whether it was present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let was_present = self.contains(value);
+        if was_present {
+            self.0 &= !(1 << value);
+        }
+        was_present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && self.0 & (1 << value) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Iterates over the contained values in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let value = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some(value)
+        })
+    }
+}
+
+impl FromIterator<usize> for BitSet128 {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for BitSet128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitSet128;
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut set = BitSet128::new();
+        assert!(set.insert(0));
+        assert!(set.insert(127));
+        assert!(!set.insert(0));
+        assert!(set.contains(0));
+        assert!(set.contains(127));
+        assert!(!set.contains(64));
+        assert!(!set.contains(500));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_out_of_range() {
+        BitSet128::new().insert(128);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: BitSet128 = [1, 5, 17, 99].into_iter().collect();
+        let b: BitSet128 = [5, 17, 100].into_iter().collect();
+
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![5, 17]);
+        assert_eq!(a.union(&b).len(), 5);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![1, 99]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: BitSet128 = [3, 4].into_iter().collect();
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert_eq!(format!("{:?}", set), "{4}");
+        assert!(set.remove(4));
+        assert!(set.is_empty());
+    }
+}
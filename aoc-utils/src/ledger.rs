@@ -0,0 +1,425 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{day_directory, find_day_file};
+
+/// One of the two parts of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    A,
+    B,
+}
+
+impl Part {
+    /// The number of the part as used by the advent of code website.
+    pub fn level(&self) -> u8 {
+        match self {
+            Part::A => 1,
+            Part::B => 2,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "a" | "1" => Some(Part::A),
+            "b" | "2" => Some(Part::B),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::A => write!(f, "A"),
+            Part::B => write!(f, "B"),
+        }
+    }
+}
+
+/// What the website said about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint in which direction.
+    Wrong,
+}
+
+impl Verdict {
+    const ALL: [Verdict; 4] = [
+        Verdict::Correct,
+        Verdict::TooHigh,
+        Verdict::TooLow,
+        Verdict::Wrong,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::Wrong => "wrong",
+        }
+    }
+
+    pub fn is_correct(&self) -> bool {
+        *self == Verdict::Correct
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub part: Part,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Submission {
+    /// Parses a line of the ledger file, e.g. `A 1234 too-high`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let part = Part::parse(fields.next()?)?;
+        let answer = fields.next()?.to_string();
+        let verdict_name = fields.next()?;
+        let verdict = Verdict::ALL
+            .into_iter()
+            .find(|verdict| verdict.name() == verdict_name)?;
+
+        fields.next().is_none().then_some(Self {
+            part,
+            answer,
+            verdict,
+        })
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.part, self.answer, self.verdict)
+    }
+}
+
+/// What the ledger knows about an answer before it is submitted or after it was calculated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    /// Nothing is known about the answer yet.
+    Unknown,
+    Accepted,
+    /// Another answer was accepted for the part.
+    Mismatch(String),
+    /// The same answer was already rejected.
+    Rejected(Verdict),
+    /// The answer is not above a guess that was too low.
+    NotAboveTooLow(String),
+    /// The answer is not below a guess that was too high.
+    NotBelowTooHigh(String),
+}
+
+impl Check {
+    /// Whether the answer is known to be wrong.
+    pub fn is_wrong(&self) -> bool {
+        !matches!(self, Check::Unknown | Check::Accepted)
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Unknown => write!(f, "not submitted yet"),
+            Check::Accepted => write!(f, "accepted"),
+            Check::Mismatch(correct) => write!(f, "the accepted answer is {}", correct),
+            Check::Rejected(verdict) => write!(f, "already rejected as {}", verdict),
+            Check::NotAboveTooLow(guess) => {
+                write!(
+                    f,
+                    "not above the previously rejected too-low guess {}",
+                    guess
+                )
+            }
+            Check::NotBelowTooHigh(guess) => {
+                write!(
+                    f,
+                    "not below the previously rejected too-high guess {}",
+                    guess
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LedgerError {
+    Io(io::Error),
+    InvalidLine(usize, String),
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::Io(e) => write!(f, "Could not access the answer ledger: {}", e),
+            LedgerError::InvalidLine(number, line) => {
+                write!(f, "Invalid line {} in the answer ledger: {}", number, line)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for LedgerError {
+    fn from(e: io::Error) -> Self {
+        LedgerError::Io(e)
+    }
+}
+
+/// The answers submitted for a day together with their verdicts, stored next to the puzzle
+/// input as answers_day<day_number>.txt with one submission per line.
+#[derive(Debug)]
+pub struct Ledger {
+    path: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl Ledger {
+    pub fn file_name(day: u8) -> String {
+        format!("answers_day{:02}.txt", day)
+    }
+
+    /// Loads the ledger from the file, which doesn't have to exist yet.
+    pub fn load<P: Into<PathBuf>>(path: P) -> Result<Self, LedgerError> {
+        let path = path.into();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let submissions = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                Submission::parse(line).ok_or(LedgerError::InvalidLine(i + 1, line.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { path, submissions })
+    }
+
+    /// Loads the ledger of the day, found the same way as the puzzle input. A day without
+    /// ledger gets an empty one in its day directory.
+    pub fn for_day(year: u16, day: u8) -> Result<Self, LedgerError> {
+        let file_name = Self::file_name(day);
+        let path = find_day_file(year, day, &file_name)
+            .unwrap_or_else(|| day_directory(year, day).join(file_name));
+        Self::load(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn submissions(&self) -> &[Submission] {
+        &self.submissions
+    }
+
+    /// The earlier submission of the same answer for the part.
+    pub fn find(&self, part: Part, answer: &str) -> Option<&Submission> {
+        self.submissions
+            .iter()
+            .find(|submission| submission.part == part && submission.answer == answer)
+    }
+
+    pub fn correct_answer(&self, part: Part) -> Option<&str> {
+        self.submissions
+            .iter()
+            .find(|submission| submission.part == part && submission.verdict.is_correct())
+            .map(|submission| submission.answer.as_str())
+    }
+
+    /// The closest rejected guesses around the correct answer of the part: the largest one
+    /// that was too low and the smallest one that was too high. Only numeric answers count.
+    pub fn bounds(&self, part: Part) -> (Option<&str>, Option<&str>) {
+        let guesses = |verdict: Verdict| {
+            self.submissions
+                .iter()
+                .filter(move |submission| submission.part == part && submission.verdict == verdict)
+                .filter_map(|submission| {
+                    let value = submission.answer.parse::<i128>().ok()?;
+                    Some((value, submission.answer.as_str()))
+                })
+        };
+        let too_low = guesses(Verdict::TooLow).max().map(|(_, answer)| answer);
+        let too_high = guesses(Verdict::TooHigh).min().map(|(_, answer)| answer);
+        (too_low, too_high)
+    }
+
+    /// Checks the answer against the earlier submissions of the part.
+    pub fn check(&self, part: Part, answer: &str) -> Check {
+        if let Some(submission) = self.find(part, answer) {
+            return match submission.verdict {
+                Verdict::Correct => Check::Accepted,
+                verdict => Check::Rejected(verdict),
+            };
+        }
+        if let Some(correct) = self.correct_answer(part) {
+            return Check::Mismatch(correct.to_string());
+        }
+
+        let Ok(value) = answer.parse::<i128>() else {
+            return Check::Unknown;
+        };
+        let (too_low, too_high) = self.bounds(part);
+        match (too_low, too_high) {
+            (Some(guess), _) if value <= guess.parse::<i128>().unwrap() => {
+                Check::NotAboveTooLow(guess.to_string())
+            }
+            (_, Some(guess)) if value >= guess.parse::<i128>().unwrap() => {
+                Check::NotBelowTooHigh(guess.to_string())
+            }
+            _ => Check::Unknown,
+        }
+    }
+
+    /// Adds the submission and appends it to the file right away.
+    pub fn record(&mut self, submission: Submission) -> Result<(), LedgerError> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", submission)?;
+        self.submissions.push(submission);
+        Ok(())
+    }
+}
+
+/// Prints the answer of a part like `A: 1234`, together with what the ledger of the day knows
+/// about it, so a run shows right away if an accepted answer changed.
+pub fn print_answer<T: fmt::Display>(year: u16, day: u8, part: Part, answer: T) {
+    let answer = answer.to_string();
+    match Ledger::for_day(year, day) {
+        Ok(ledger) => match ledger.check(part, &answer) {
+            Check::Unknown => println!("{}: {}", part, answer),
+            check if check.is_wrong() => println!("{}: {} (wrong, {})", part, answer, check),
+            check => println!("{}: {} ({})", part, answer, check),
+        },
+        Err(e) => {
+            println!("{}: {}", part, answer);
+            eprintln!("{}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ledger-{}-{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let path = test_path("record");
+        let mut ledger = Ledger::load(&path).unwrap();
+        assert!(ledger.submissions().is_empty());
+
+        for (part, answer, verdict) in [
+            (Part::A, "2000", Verdict::TooLow),
+            (Part::A, "3000", Verdict::Correct),
+            (Part::B, "abc", Verdict::Wrong),
+        ] {
+            let answer = answer.to_string();
+            ledger
+                .record(Submission {
+                    part,
+                    answer,
+                    verdict,
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "A 2000 too-low\nA 3000 correct\nB abc wrong\n"
+        );
+
+        let ledger = Ledger::load(&path).unwrap();
+        assert_eq!(ledger.submissions().len(), 3);
+        assert_eq!(ledger.correct_answer(Part::A), Some("3000"));
+        assert_eq!(ledger.correct_answer(Part::B), None);
+        assert_eq!(
+            ledger.find(Part::A, "2000").map(|s| s.verdict),
+            Some(Verdict::TooLow)
+        );
+        assert_eq!(ledger.find(Part::B, "2000"), None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_check() {
+        let path = test_path("check");
+        fs::write(
+            &path,
+            "A 100 too-high\nA 20 too-low\nA 60 too-high\nA 40 too-low\nA x wrong\nB 7 correct\n",
+        )
+        .unwrap();
+        let ledger = Ledger::load(&path).unwrap();
+
+        assert_eq!(ledger.bounds(Part::A), (Some("40"), Some("60")));
+        assert_eq!(ledger.bounds(Part::B), (None, None));
+        assert_eq!(ledger.check(Part::A, "50"), Check::Unknown);
+        assert_eq!(ledger.check(Part::A, "abc"), Check::Unknown);
+        assert_eq!(ledger.check(Part::A, "x"), Check::Rejected(Verdict::Wrong));
+        assert_eq!(
+            ledger.check(Part::A, "40"),
+            Check::Rejected(Verdict::TooLow)
+        );
+        assert_eq!(
+            ledger.check(Part::A, "30"),
+            Check::NotAboveTooLow("40".to_string())
+        );
+        assert_eq!(
+            ledger.check(Part::A, "70"),
+            Check::NotBelowTooHigh("60".to_string())
+        );
+        assert_eq!(ledger.check(Part::B, "7"), Check::Accepted);
+        assert_eq!(ledger.check(Part::B, "8"), Check::Mismatch("7".to_string()));
+        assert!(ledger.check(Part::B, "8").is_wrong());
+        assert_eq!(
+            ledger.check(Part::A, "10").to_string(),
+            "not above the previously rejected too-low guess 40"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_ledger() {
+        let path = test_path("invalid");
+        fs::write(&path, "# part answer verdict\nA 1 correct\n\nC 1 wrong\n").unwrap();
+        assert!(matches!(
+            Ledger::load(&path),
+            Err(LedgerError::InvalidLine(4, _))
+        ));
+        fs::write(&path, "A 1 maybe\n").unwrap();
+        assert!(Ledger::load(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_part() {
+        assert_eq!(Part::parse("a"), Some(Part::A));
+        assert_eq!(Part::parse("2"), Some(Part::B));
+        assert_eq!(Part::parse("3"), None);
+        assert_eq!(Part::B.level(), 2);
+    }
+}
@@ -1,6 +1,39 @@
 use std::fs;
 use std::iter::Map;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+pub mod bitset;
+pub mod ledger;
+pub mod sequence;
+
+/// The year whose days live directly in the workspace root as `dayNN`.
+pub const DEFAULT_YEAR: u16 = 2023;
+
+/// The directory of a day relative to the workspace root: `dayNN` for the [`DEFAULT_YEAR`]
+/// and `YYYY/dayNN` for every other year.
+pub fn day_directory(year: u16, day: u8) -> PathBuf {
+    let day_dir = format!("day{:02}", day);
+    if year == DEFAULT_YEAR {
+        PathBuf::from(day_dir)
+    } else {
+        Path::new(&year.to_string()).join(day_dir)
+    }
+}
+
+/// Finds a file of a day. When running in tests, the working directory is inside the package,
+/// but when running the actual puzzle, it's in the workspace root.
+pub fn find_day_file(year: u16, day: u8, file_name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(file_name);
+    let outside_path = day_directory(year, day).join(file_name);
+
+    if path.exists() {
+        Some(path)
+    } else if outside_path.exists() {
+        Some(outside_path)
+    } else {
+        None
+    }
+}
 
 pub struct PuzzleInput {
     pub raw_input: String,
@@ -13,40 +46,39 @@ impl PuzzleInput {
         }
     }
 
-    /// Reads the puzzle input for the puzzle with the given day from input_day<day_number>.txt
+    /// Reads the puzzle input for the puzzle with the given day of the [`DEFAULT_YEAR`]
+    /// from input_day<day_number>.txt
     pub fn get_input(day: u8) -> PuzzleInput {
-        let path_string = format!("input_day{:02}.txt", day);
-        let path = Path::new(&path_string);
-
-        // When running in tests, the working directory is inside the package, but
-        // when running the actual puzzle, it's in the workspace root.
-        let outside_path_str = format!("day{:02}/{}", day, path.display());
-        let outside_path = Path::new(&outside_path_str);
-
-        if path.exists() {
-            let content = fs::read_to_string(path).expect("Unable to read file");
-            PuzzleInput::new(content)
-        } else if outside_path.exists() {
-            let content = fs::read_to_string(outside_path).expect("Unable to read file");
-            PuzzleInput::new(content)
-        } else {
-            let cwd = std::env::current_dir().unwrap();
-            panic!(
-                "Puzzle input at {}/{} does not exist",
-                cwd.display(),
-                path.display()
-            );
+        Self::get_input_for_year(DEFAULT_YEAR, day)
+    }
+
+    /// Reads the puzzle input for the puzzle with the given year and day from
+    /// input_day<day_number>.txt, see [`day_directory`] for where the day is located.
+    pub fn get_input_for_year(year: u16, day: u8) -> PuzzleInput {
+        let file_name = format!("input_day{:02}.txt", day);
+
+        match find_day_file(year, day, &file_name) {
+            Some(path) => {
+                let content = fs::read_to_string(path).expect("Unable to read file");
+                PuzzleInput::new(content)
+            }
+            None => {
+                let cwd = std::env::current_dir().unwrap();
+                panic!(
+                    "Puzzle input at {}/{} does not exist",
+                    cwd.display(),
+                    file_name
+                );
+            }
         }
     }
 
-    pub fn lines(&self) -> Map<std::str::Lines, fn(&str) -> String> {
+    pub fn lines(&self) -> Map<std::str::Lines<'_>, fn(&str) -> String> {
         self.raw_input.lines().map(|s| s.to_string())
     }
 
     pub fn convert_to_ints_by_line<S: std::str::FromStr>(&self) -> Vec<S> {
-        self.lines()
-            .filter_map(|s| s.parse::<S>().ok())
-            .collect()
+        self.lines().filter_map(|s| s.parse::<S>().ok()).collect()
     }
 
     pub fn convert_to_ints<S: std::str::FromStr>(&self) -> Vec<S> {
@@ -73,4 +105,17 @@ mod tests {
     fn test_get_input_1b_fail() {
         super::PuzzleInput::get_input(1);
     }
+
+    #[test]
+    #[should_panic]
+    fn test_get_input_for_year_fail() {
+        super::PuzzleInput::get_input_for_year(2015, 1);
+    }
+
+    #[test]
+    fn test_day_directory() {
+        use std::path::Path;
+        assert_eq!(super::day_directory(2023, 7), Path::new("day07"));
+        assert_eq!(super::day_directory(2024, 12), Path::new("2024/day12"));
+    }
 }
@@ -0,0 +1,444 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Builds the table of finite differences of the values. The first row are the values
+/// themselves, every following row the differences of adjacent values of the row before.
+/// Stops once a row only contains zeros, which is also the case for an empty row.
+/// Returns `None` if a difference doesn't fit into an `i64`.
+pub fn difference_table(values: &[i64]) -> Option<Vec<Vec<i64>>> {
+    let mut table = vec![values.to_vec()];
+
+    while !table.last().unwrap().iter().all(|v| *v == 0) {
+        let differences = table
+            .last()
+            .unwrap()
+            .windows(2)
+            .map(|pair| pair[1].checked_sub(pair[0]))
+            .collect::<Option<_>>()?;
+        table.push(differences);
+    }
+
+    Some(table)
+}
+
+/// Detects the degree of the polynomial the values follow, if there are enough values to be
+/// certain: the differences of that order have to be constant over at least two values.
+/// A constant sequence has degree 0. Returns `None` if the differences overflow too.
+pub fn polynomial_degree(values: &[i64]) -> Option<usize> {
+    difference_table(values)?
+        .iter()
+        .position(|row| row.len() >= 2 && row.iter().all(|v| *v == row[0]))
+}
+
+const OVERFLOW: &str = "Rational overflow, the result doesn't fit into i128";
+
+/// The greatest common divisor, `None` if computing it overflows, which needs `i128::MIN`.
+fn gcd(a: i128, b: i128) -> Option<i128> {
+    if b == 0 {
+        a.checked_abs()
+    } else {
+        gcd(b, a.checked_rem(b)?)
+    }
+}
+
+/// An exact fraction, always stored in lowest terms with a positive denominator.
+///
+/// The operators panic if a result or an intermediate product doesn't fit into an `i128`,
+/// in release builds too. The `checked_*` methods return `None` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Rational = Rational {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Panics if the denominator is zero or the fraction can't be reduced within `i128`,
+    /// like `i128::MIN / -1`.
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert!(denominator != 0, "Denominator must not be zero");
+        Self::checked_new(numerator, denominator).expect(OVERFLOW)
+    }
+
+    /// Like [`Rational::new`], but returns `None` instead of panicking.
+    pub fn checked_new(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator)? * denominator.signum();
+        Some(Self {
+            numerator: numerator.checked_div(divisor)?,
+            denominator: denominator.checked_div(divisor)?,
+        })
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Self::checked_new(
+            self.numerator
+                .checked_mul(other.denominator)?
+                .checked_add(other.numerator.checked_mul(self.denominator)?)?,
+            self.denominator.checked_mul(other.denominator)?,
+        )
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Self::checked_new(
+            self.numerator.checked_mul(other.numerator)?,
+            self.denominator.checked_mul(other.denominator)?,
+        )
+    }
+
+    /// Returns `None` when dividing by zero too.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        Self::checked_new(
+            self.numerator.checked_mul(other.denominator)?,
+            self.denominator.checked_mul(other.numerator)?,
+        )
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    /// Returns the value as an integer if it is one.
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numerator)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 1)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Self::new(value, 1)
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other).expect(OVERFLOW)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(other).expect(OVERFLOW)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(other).expect(OVERFLOW)
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    /// Panics when dividing by zero.
+    fn div(self, other: Self) -> Self {
+        assert!(other != Self::ZERO, "Division by zero");
+        self.checked_div(other).expect(OVERFLOW)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.checked_neg().expect(OVERFLOW)
+    }
+}
+
+impl Ord for Rational {
+    /// Compares the integer parts first and then the reciprocals of the remainders, like
+    /// continued fractions, so unlike cross multiplying nothing can overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.numerator, self.denominator);
+        let (mut c, mut d) = (other.numerator, other.denominator);
+        // Comparing the reciprocals reverses the order
+        let mut reversed = false;
+
+        loop {
+            let ordering = match a.div_euclid(b).cmp(&c.div_euclid(d)) {
+                Ordering::Equal => match (a.rem_euclid(b), c.rem_euclid(d)) {
+                    (0, 0) => Ordering::Equal,
+                    (0, _) => Ordering::Less,
+                    (_, 0) => Ordering::Greater,
+                    (r1, r2) => {
+                        (a, b, c, d) = (b, r1, d, r2);
+                        reversed = !reversed;
+                        continue;
+                    }
+                },
+                ordering => ordering,
+            };
+            return if reversed {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+/// A polynomial with exact rational coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// Coefficients from the constant term up to the highest power, without trailing zeros.
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Rational>) -> Self {
+        while coefficients.last() == Some(&Rational::ZERO) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    /// Finds the polynomial of lowest degree going through all points using Lagrange
+    /// interpolation. Returns `None` if two points share the same x value.
+    pub fn interpolate(points: &[(Rational, Rational)]) -> Option<Self> {
+        let mut coefficients = vec![Rational::ZERO; points.len()];
+
+        for (i, (x_i, y_i)) in points.iter().enumerate() {
+            // Basis polynomial that is 1 at x_i and 0 at every other x
+            let mut basis = vec![Rational::ONE];
+            for (j, (x_j, _)) in points.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if x_i == x_j {
+                    return None;
+                }
+
+                let scale = Rational::ONE / (*x_i - *x_j);
+                let mut product = vec![Rational::ZERO; basis.len() + 1];
+                for (power, coefficient) in basis.iter().enumerate() {
+                    product[power + 1] = product[power + 1] + *coefficient * scale;
+                    product[power] = product[power] - *coefficient * *x_j * scale;
+                }
+                basis = product;
+            }
+
+            for (power, coefficient) in basis.iter().enumerate() {
+                coefficients[power] = coefficients[power] + *coefficient * *y_i;
+            }
+        }
+
+        Some(Self::new(coefficients))
+    }
+
+    /// Interpolates the values of a sequence, with the first value at position 0.
+    pub fn from_sequence(values: &[i64]) -> Self {
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(x, y)| (Rational::from(x as i64), Rational::from(*y)))
+            .collect::<Vec<_>>();
+        Self::interpolate(&points).unwrap()
+    }
+
+    pub fn coefficients(&self) -> &[Rational] {
+        &self.coefficients
+    }
+
+    /// The degree of the polynomial, `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn evaluate(&self, x: Rational) -> Rational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Rational::ZERO, |result, coefficient| {
+                result * x + *coefficient
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINEAR: [i64; 6] = [0, 3, 6, 9, 12, 15];
+    const QUADRATIC: [i64; 6] = [1, 3, 6, 10, 15, 21];
+    const CUBIC: [i64; 6] = [10, 13, 16, 21, 30, 45];
+
+    #[test]
+    fn test_difference_table() {
+        assert_eq!(
+            difference_table(&LINEAR),
+            Some(vec![LINEAR.to_vec(), vec![3; 5], vec![0; 4]])
+        );
+        assert_eq!(difference_table(&[]), Some(vec![Vec::<i64>::new()]));
+        assert_eq!(difference_table(&[5]), Some(vec![vec![5], vec![]]));
+    }
+
+    #[test]
+    fn test_difference_table_overflow() {
+        assert_eq!(difference_table(&[i64::MIN, i64::MAX]), None);
+        assert_eq!(polynomial_degree(&[i64::MIN, i64::MAX]), None);
+        assert_eq!(
+            difference_table(&[i64::MAX, i64::MAX]),
+            Some(vec![vec![i64::MAX; 2], vec![0]])
+        );
+    }
+
+    #[test]
+    fn test_polynomial_degree() {
+        assert_eq!(polynomial_degree(&[7, 7, 7]), Some(0));
+        assert_eq!(polynomial_degree(&LINEAR), Some(1));
+        assert_eq!(polynomial_degree(&QUADRATIC), Some(2));
+        assert_eq!(polynomial_degree(&CUBIC), Some(3));
+        // Not enough values to confirm the third differences are constant
+        assert_eq!(polynomial_degree(&CUBIC[..4]), None);
+        assert_eq!(polynomial_degree(&[]), None);
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(1, 2);
+        let third = Rational::new(-2, -6);
+        assert_eq!(third, Rational::new(1, 3));
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert_eq!(Rational::new(4, -2), Rational::from(-2i64));
+        assert_eq!(Rational::new(4, -2).to_integer(), Some(-2));
+        assert_eq!(half.to_integer(), None);
+        assert!(third < half);
+        assert_eq!(Rational::new(-3, 6).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn test_rational_overflow() {
+        let max = Rational::from(i128::MAX);
+        assert_eq!(max.checked_add(Rational::ONE), None);
+        assert_eq!(max.checked_mul(Rational::from(2i64)), None);
+        assert_eq!(Rational::from(i128::MIN).checked_neg(), None);
+        assert_eq!(Rational::checked_new(i128::MIN, -1), None);
+        assert_eq!(Rational::ONE.checked_div(Rational::ZERO), None);
+        assert_eq!(
+            max.checked_sub(Rational::ONE),
+            Some(Rational::from(i128::MAX - 1))
+        );
+
+        // Cross multiplying these would overflow
+        let almost_half = Rational::new(i128::MAX - 2, i128::MAX);
+        let half = Rational::new(1, 2);
+        assert!(almost_half > half);
+        assert!(Rational::new(i128::MAX / 2, i128::MAX) < half);
+        assert!(Rational::new(-7, 3) < Rational::new(-9, 4));
+        assert!(Rational::new(5, 3) > Rational::new(8, 5));
+        assert_eq!(
+            Rational::new(3, 7).cmp(&Rational::new(3, 7)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Rational overflow")]
+    fn test_rational_operator_overflow() {
+        let _ = Rational::from(i128::MAX) + Rational::ONE;
+    }
+
+    #[test]
+    fn test_interpolate_sequences() {
+        for (values, next, previous) in [(LINEAR, 18, -3), (QUADRATIC, 28, 0), (CUBIC, 68, 5)] {
+            let polynomial = Polynomial::from_sequence(&values);
+            assert_eq!(polynomial.degree(), polynomial_degree(&values));
+            assert_eq!(
+                polynomial.evaluate(Rational::from(6i64)),
+                Rational::from(next as i64)
+            );
+            assert_eq!(
+                polynomial.evaluate(Rational::from(-1i64)),
+                Rational::from(previous as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn test_interpolate_points() {
+        // y = x^2 / 2 at arbitrary x values
+        let points = [(1, 2), (-3, 2), (4, 1)].map(|(numerator, denominator)| {
+            let x = Rational::new(numerator, denominator);
+            (x, x * x / Rational::from(2i64))
+        });
+        let polynomial = Polynomial::interpolate(&points).unwrap();
+        assert_eq!(
+            polynomial.coefficients(),
+            &[Rational::ZERO, Rational::ZERO, Rational::new(1, 2)]
+        );
+        assert_eq!(
+            polynomial.evaluate(Rational::from(26501365i64)),
+            Rational::new(26501365 * 26501365, 2)
+        );
+
+        let duplicate = [
+            (Rational::ONE, Rational::ONE),
+            (Rational::ONE, Rational::ZERO),
+        ];
+        assert_eq!(Polynomial::interpolate(&duplicate), None);
+        assert_eq!(Polynomial::interpolate(&[]), Some(Polynomial::new(vec![])));
+        assert_eq!(Polynomial::new(vec![]).degree(), None);
+    }
+}
@@ -1,15 +1,276 @@
-use std::vec;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
 
-use aoc_utils::PuzzleInput;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 1;
 const SPELLED_DIGITS: [&str; 10] = [
     "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
+const GERMAN_DIGITS: [&str; 10] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const ROMAN_DIGITS: [&str; 10] = ["", "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix"];
+const NUMERAL_DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+#[derive(Debug, PartialEq, Eq)]
+enum VocabularyError {
+    EmptyWord,
+    InvalidValue(String, usize),
+    Conflict(String, usize, usize),
+    InvalidLine(String),
+    Io(String),
+}
+
+impl fmt::Display for VocabularyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyWord => write!(f, "Vocabulary contains an empty word"),
+            Self::InvalidValue(word, value) => {
+                write!(f, "Value {} of word {} is not a single digit", value, word)
+            }
+            Self::Conflict(word, a, b) => {
+                write!(f, "Word {} is used for both {} and {}", word, a, b)
+            }
+            Self::InvalidLine(line) => write!(f, "Invalid vocabulary line: {}", line),
+            Self::Io(e) => write!(f, "Could not read vocabulary: {}", e),
+        }
+    }
+}
+
+/// Words that are recognized as digits.
+///
+/// Every word maps to exactly one digit, comparing words case insensitively. Words may overlap
+/// or be prefixes of each other, if multiple words start at the same position the longest wins.
+#[derive(Debug)]
+struct Vocabulary {
+    words: Vec<(String, usize)>,
+}
+
+impl Vocabulary {
+    fn new<S: Into<String>>(
+        words: impl IntoIterator<Item = (S, usize)>,
+    ) -> Result<Self, VocabularyError> {
+        let mut vocabulary = Self { words: Vec::new() };
+
+        for (word, value) in words {
+            let word = word.into().to_ascii_lowercase();
+            if word.is_empty() {
+                return Err(VocabularyError::EmptyWord);
+            }
+            if value > 9 {
+                return Err(VocabularyError::InvalidValue(word, value));
+            }
+
+            match vocabulary.words.iter().find(|(w, _)| *w == word) {
+                Some((_, existing)) if *existing != value => {
+                    return Err(VocabularyError::Conflict(word, *existing, value));
+                }
+                Some(_) => {}
+                None => vocabulary.words.push((word, value)),
+            }
+        }
+
+        Ok(vocabulary)
+    }
+
+    /// Builds a vocabulary of the numerals and the given words for each digit.
+    /// Empty words are skipped, so digits without a name can be left out.
+    fn with_numerals(names: &[&str; 10]) -> Self {
+        let words = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(i, name)| (*name, i))
+            .chain(NUMERAL_DIGITS.iter().enumerate().map(|(i, n)| (*n, i)));
+        Self::new(words).unwrap()
+    }
+
+    fn numerals() -> Self {
+        Self::with_numerals(&[""; 10])
+    }
+
+    fn english() -> Self {
+        Self::with_numerals(&SPELLED_DIGITS)
+    }
+
+    fn german() -> Self {
+        Self::with_numerals(&GERMAN_DIGITS)
+    }
+
+    fn roman() -> Self {
+        Self::with_numerals(&ROMAN_DIGITS)
+    }
+
+    /// Parses a word list with one `<word> <digit>` pair per line in addition to the numerals.
+    /// Empty lines and lines starting with `#` are ignored.
+    fn parse(content: &str) -> Result<Self, VocabularyError> {
+        let mut words = NUMERAL_DIGITS
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.to_string(), i))
+            .collect::<Vec<_>>();
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, value) = line
+                .rsplit_once(char::is_whitespace)
+                .and_then(|(word, value)| Some((word.trim(), value.parse().ok()?)))
+                .ok_or_else(|| VocabularyError::InvalidLine(line.to_string()))?;
+            words.push((word.to_string(), value));
+        }
+
+        Self::new(words)
+    }
+
+    /// Returns one of the built in vocabularies or reads a word list from the given path.
+    fn load(name: &str) -> Result<Self, VocabularyError> {
+        match name {
+            "numerals" => Ok(Self::numerals()),
+            "english" => Ok(Self::english()),
+            "german" => Ok(Self::german()),
+            "roman" => Ok(Self::roman()),
+            path => {
+                let content =
+                    fs::read_to_string(path).map_err(|e| VocabularyError::Io(e.to_string()))?;
+                Self::parse(&content)
+            }
+        }
+    }
+}
+
+/// Aho-Corasick automaton that finds digit words in a line in a single pass.
+///
+/// Matching is case insensitive and overlapping words are all reported,
+/// so e.g. `oneight` contains both a one and an eight.
+/// Non ASCII characters are matched byte wise.
+struct DigitScanner {
+    /// Complete transition table, indexed by state and input byte.
+    transitions: Vec<[u32; 256]>,
+    /// Length and value of the word ending exactly in this state.
+    outputs: Vec<Option<(usize, usize)>>,
+    /// Nearest state reachable via failure links that has an output.
+    dictionary_links: Vec<Option<usize>>,
+}
+
+impl DigitScanner {
+    fn new(vocabulary: &Vocabulary) -> Self {
+        let mut transitions = vec![[0; 256]];
+        let mut outputs = vec![None];
+
+        for (word, value) in vocabulary.words.iter() {
+            let mut state = 0;
+            for byte in word.bytes() {
+                let next = transitions[state][byte as usize] as usize;
+                state = if next == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(None);
+                    let new_state = transitions.len() - 1;
+                    transitions[state][byte as usize] = new_state as u32;
+                    new_state
+                } else {
+                    next
+                };
+            }
+            outputs[state] = Some((word.len(), *value));
+        }
+
+        // Breadth first over the trie, so failure links always point to already finished states.
+        // Missing transitions are replaced by the transition of the failure state.
+        let mut failure_links = vec![0; transitions.len()];
+        let mut dictionary_links = vec![None; transitions.len()];
+        let mut queue = transitions[0]
+            .iter()
+            .filter(|&&next| next != 0)
+            .map(|&next| next as usize)
+            .collect::<VecDeque<_>>();
+
+        while let Some(state) = queue.pop_front() {
+            let failure = failure_links[state];
+            let row = transitions[state];
+            for (byte, next) in row.into_iter().enumerate() {
+                let next = next as usize;
+                if next == 0 {
+                    transitions[state][byte] = transitions[failure][byte];
+                } else {
+                    let next_failure = transitions[failure][byte] as usize;
+                    failure_links[next] = next_failure;
+                    dictionary_links[next] = if outputs[next_failure].is_some() {
+                        Some(next_failure)
+                    } else {
+                        dictionary_links[next_failure]
+                    };
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Self {
+            transitions,
+            outputs,
+            dictionary_links,
+        }
+    }
+
+    /// Returns the values of the words starting leftmost and rightmost in the line.
+    fn first_and_last(&self, line: &str) -> Option<(usize, usize)> {
+        // (start, length, value) of the matches
+        let mut first: Option<(usize, usize, usize)> = None;
+        let mut last: Option<(usize, usize, usize)> = None;
+        let mut state = 0;
+
+        for (end, byte) in line.bytes().enumerate() {
+            state = self.transitions[state][byte.to_ascii_lowercase() as usize] as usize;
+
+            let mut match_state = if self.outputs[state].is_some() {
+                Some(state)
+            } else {
+                self.dictionary_links[state]
+            };
+            while let Some(s) = match_state {
+                let (len, value) = self.outputs[s].unwrap();
+                let start = end + 1 - len;
+                if first.is_none_or(|(s, l, _)| start < s || (start == s && len > l)) {
+                    first = Some((start, len, value));
+                }
+                if last.is_none_or(|(s, l, _)| start > s || (start == s && len > l)) {
+                    last = Some((start, len, value));
+                }
+                match_state = self.dictionary_links[s];
+            }
+        }
+
+        Some((first?.2, last?.2))
+    }
+}
 
 fn main() {
+    let vocabulary = match std::env::args().skip(1).collect::<Vec<_>>().as_slice() {
+        [] => None,
+        [flag, name] if flag == "--vocabulary" => {
+            Some(Vocabulary::load(name).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }))
+        }
+        _ => {
+            eprintln!("Usage: day01 [--vocabulary numerals|english|german|roman|<word list file>]");
+            std::process::exit(1);
+        }
+    };
+
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
+    if let Some(vocabulary) = vocabulary {
+        println!(
+            "Calibration sum with custom vocabulary: {}",
+            calibration_sum(&input, &vocabulary)
+        );
+    }
 }
 
 fn solve_a(input: &PuzzleInput) -> usize {
@@ -25,51 +286,23 @@ fn solve_a(input: &PuzzleInput) -> usize {
         .sum()
 }
 
-fn get_digits(line_original: String) -> Vec<usize> {
-    let mut line = line_original.clone().to_lowercase();
-    let mut digits = vec![];
-
-    while !line.is_empty() {
-        let mut found_spelled_digit = false;
-        for (i, spelled_digit) in SPELLED_DIGITS.iter().enumerate() {
-            if line.starts_with(spelled_digit) {
-                digits.push(i);
-                line = line[spelled_digit.len()..].to_string();
-                found_spelled_digit = true;
-                break;
-            }
-            // Handle overlapping spelled digits
-            if line.starts_with(&spelled_digit[1..]) {
-                digits.push(i);
-                line = line[spelled_digit[1..].len()..].to_string();
-                found_spelled_digit = true;
-                break;
-            }
-        }
-        if found_spelled_digit {
-            continue;
-        }
-
-        let c = line.chars().next().unwrap();
-        if c.is_ascii_digit() {
-            digits.push(c.to_digit(10).unwrap() as usize);
-        }
-
-        line = line[1..].to_string();
-    }
-    println!("{line_original} {:?}", digits);
-
-    digits
-}
-
-fn solve_b(input: &PuzzleInput) -> usize {
+fn calibration_sum(input: &PuzzleInput, vocabulary: &Vocabulary) -> usize {
+    let scanner = DigitScanner::new(vocabulary);
     input
         .lines()
-        .map(get_digits)
-        .map(|digits| digits[0] * 10 + digits[digits.len() - 1])
+        .map(|line| {
+            scanner
+                .first_and_last(&line)
+                .unwrap_or_else(|| panic!("No digit in line {}", line))
+        })
+        .map(|(first, last)| first * 10 + last)
         .sum()
 }
 
+fn solve_b(input: &PuzzleInput) -> usize {
+    calibration_sum(input, &Vocabulary::english())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +327,132 @@ zoneight234
         solve_b(&input);
     }
 
+    /// Finds the digits by checking every word at every position of the line.
+    fn first_and_last_naive(vocabulary: &Vocabulary, line: &str) -> Option<(usize, usize)> {
+        let line = line.to_lowercase();
+        let digits = (0..line.len())
+            .flat_map(|i| {
+                vocabulary
+                    .words
+                    .iter()
+                    .filter(|(word, _)| line.as_bytes()[i..].starts_with(word.as_bytes()))
+                    .max_by_key(|(word, _)| word.len())
+                    .map(|(_, value)| *value)
+            })
+            .collect::<Vec<_>>();
+        Some((*digits.first()?, *digits.last()?))
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let scanner = DigitScanner::new(&Vocabulary::english());
+        assert_eq!(scanner.first_and_last("oneight"), Some((1, 8)));
+        assert_eq!(scanner.first_and_last("twone"), Some((2, 1)));
+        assert_eq!(scanner.first_and_last("eightwothree"), Some((8, 3)));
+        assert_eq!(scanner.first_and_last("sevenine"), Some((7, 9)));
+        assert_eq!(scanner.first_and_last("xFIVEx"), Some((5, 5)));
+        assert_eq!(scanner.first_and_last("7"), Some((7, 7)));
+        assert_eq!(scanner.first_and_last("onon"), None);
+    }
+
+    #[test]
+    fn test_scanner_matches_naive() {
+        let vocabulary = Vocabulary::english();
+        let scanner = DigitScanner::new(&vocabulary);
+
+        for line in TEST_INPUT_B.lines().chain([
+            "oneightwoneighthreeightwo",
+            "ninineeightt",
+            "zerone2seveneight",
+            "fivefiveight",
+            "thrthreee",
+        ]) {
+            assert_eq!(
+                scanner.first_and_last(line),
+                first_and_last_naive(&vocabulary, line),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let german = DigitScanner::new(&Vocabulary::german());
+        assert_eq!(german.first_and_last("zweinsiebenull"), Some((2, 0)));
+        assert_eq!(german.first_and_last("xfünfachtx"), Some((5, 8)));
+
+        let roman = DigitScanner::new(&Vocabulary::roman());
+        assert_eq!(roman.first_and_last("xviiix"), Some((8, 9)));
+        assert_eq!(roman.first_and_last("ivi"), Some((4, 1)));
+        assert_eq!(roman.first_and_last("abc"), None);
+
+        let numerals = DigitScanner::new(&Vocabulary::numerals());
+        assert_eq!(numerals.first_and_last("one2three"), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_parse_vocabulary() {
+        let vocabulary = Vocabulary::parse("# Spanish\nuno 1\n\ndos 2\nUNO 1").unwrap();
+        let scanner = DigitScanner::new(&vocabulary);
+        assert_eq!(scanner.first_and_last("xdosunox"), Some((2, 1)));
+        assert_eq!(scanner.first_and_last("dos3"), Some((2, 3)));
+
+        assert_eq!(
+            Vocabulary::parse("uno 1\nUno 2").unwrap_err(),
+            VocabularyError::Conflict("uno".to_string(), 1, 2)
+        );
+        assert_eq!(
+            Vocabulary::parse("diez 10").unwrap_err(),
+            VocabularyError::InvalidValue("diez".to_string(), 10)
+        );
+        assert_eq!(
+            Vocabulary::parse("uno").unwrap_err(),
+            VocabularyError::InvalidLine("uno".to_string())
+        );
+        assert_eq!(
+            Vocabulary::new([("", 1)]).unwrap_err(),
+            VocabularyError::EmptyWord
+        );
+        assert!(matches!(
+            Vocabulary::load("does/not/exist.txt"),
+            Err(VocabularyError::Io(_))
+        ));
+    }
+
+    /// Every string over `ab` up to length 12 against a vocabulary where nearly every word
+    /// overlaps with or is a prefix of another one.
+    #[test]
+    fn test_nasty_overlaps_match_naive() {
+        let vocabulary = Vocabulary::new([
+            ("a", 1),
+            ("aa", 2),
+            ("aab", 3),
+            ("ab", 4),
+            ("aba", 5),
+            ("babab", 6),
+            ("bb", 7),
+            ("bbbb", 8),
+            ("baab", 9),
+        ])
+        .unwrap();
+        let scanner = DigitScanner::new(&vocabulary);
+
+        for len in 0..=12 {
+            for bits in 0..1u32 << len {
+                let line = (0..len)
+                    .map(|i| if bits & (1 << i) == 0 { 'a' } else { 'b' })
+                    .collect::<String>();
+                assert_eq!(
+                    scanner.first_and_last(&line),
+                    first_and_last_naive(&vocabulary, &line),
+                    "{}",
+                    line
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_solve_a() {
         assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT_A)), 142);
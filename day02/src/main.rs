@@ -1,26 +1,97 @@
-use aoc_utils::PuzzleInput;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 2;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum CubeColor {
-    Blue,
-    Red,
-    Green,
+/// The cube colors used by the puzzle.
+const DEFAULT_COLORS: [&str; 3] = ["red", "green", "blue"];
+/// The bag contents given in part A of the puzzle.
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+const USAGE: &str =
+    "Usage: day02 [--colors red,green,blue] [--bag \"12 red, 13 green, 14 blue\"] [--budget <cubes>]";
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    InvalidGame(String),
+    InvalidCube(String),
+    UnknownColor(String),
 }
 
-impl CubeColor {
-    fn parse(s: &str) -> Self {
-        match s {
-            "blue" => Self::Blue,
-            "red" => Self::Red,
-            "green" => Self::Green,
-            _ => panic!("Invalid color"),
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGame(s) => write!(f, "Invalid game: {}", s),
+            Self::InvalidCube(s) => write!(f, "Invalid cube count: {}", s),
+            Self::UnknownColor(s) => write!(f, "Unknown cube color: {}", s),
         }
     }
 }
 
+/// Number of cubes per color. Colors that are not present have a count of zero.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct CubeSet {
-    cubes: Vec<CubeColor>,
+    counts: BTreeMap<String, usize>,
+}
+
+impl CubeSet {
+    /// Parses a set like `3 blue, 4 red`. Colors not contained in `colors` are rejected.
+    fn parse(s: &str, colors: &[&str]) -> Result<Self, ParseError> {
+        let mut counts = BTreeMap::new();
+        for cube in s.split(", ") {
+            let (count, color) = cube
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| ParseError::InvalidCube(cube.to_string()))?;
+            let count: usize = count
+                .parse()
+                .map_err(|_| ParseError::InvalidCube(cube.to_string()))?;
+            if !colors.contains(&color) {
+                return Err(ParseError::UnknownColor(color.to_string()));
+            }
+            *counts.entry(color.to_string()).or_insert(0) += count;
+        }
+
+        Ok(Self { counts })
+    }
+
+    fn count(&self, color: &str) -> usize {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
+
+    /// Returns the set with the larger count of both sets for each color.
+    fn max(&self, other: &Self) -> Self {
+        let mut counts = self.counts.clone();
+        for (color, &count) in other.counts.iter() {
+            let entry = counts.entry(color.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+        Self { counts }
+    }
+
+    fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Whether every color of `other` is available at least as often in this set.
+    fn contains(&self, other: &Self) -> bool {
+        other
+            .counts
+            .iter()
+            .all(|(color, &count)| self.count(color) >= count)
+    }
+}
+
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self
+            .counts
+            .iter()
+            .map(|(color, count)| format!("{} {}", count, color))
+            .collect::<Vec<_>>();
+        write!(f, "{}", parts.join(", "))
+    }
 }
 
 struct Game {
@@ -29,75 +100,230 @@ struct Game {
 }
 
 impl Game {
-    fn parse(line: &str) -> Self {
-        let mut parts = line.split(": ");
-        let id = parts
-            .next()
-            .unwrap()
-            .split(' ')
-            .nth(1)
-            .unwrap()
-            .parse()
-            .unwrap();
-
-        let mut cube_sets = Vec::new();
-        for cube_set in parts.next().unwrap().split("; ") {
-            let mut cubes = Vec::new();
-            for cube in cube_set.split(", ") {
-                let mut parts = cube.split(' ');
-                let count = parts.next().unwrap().parse().unwrap();
-                let color = CubeColor::parse(parts.next().unwrap());
-                for _ in 0..count {
-                    cubes.push(color);
-                }
-            }
-            cube_sets.push(CubeSet { cubes });
-        }
+    fn parse(line: &str, colors: &[&str]) -> Result<Self, ParseError> {
+        let invalid_game = || ParseError::InvalidGame(line.to_string());
+        let (id_part, sets_part) = line.split_once(": ").ok_or_else(invalid_game)?;
+        let id = id_part
+            .strip_prefix("Game ")
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(invalid_game)?;
+
+        let cube_sets = sets_part
+            .split("; ")
+            .map(|cube_set| CubeSet::parse(cube_set, colors))
+            .collect::<Result<_, _>>()?;
 
-        Self { id, cube_sets }
+        Ok(Self { id, cube_sets })
     }
 
-    fn minimum_needed_cubes(&self, color: CubeColor) -> usize {
+    fn minimum_needed_cubes(&self) -> CubeSet {
         self.cube_sets
             .iter()
-            .map(|cube_set| cube_set.cubes.iter().filter(|c| **c == color).count())
-            .max()
-            .unwrap()
+            .fold(CubeSet::default(), |needed, cube_set| needed.max(cube_set))
     }
 
-    fn can_be_played(&self) -> bool {
-        self.minimum_needed_cubes(CubeColor::Blue) <= 14
-            && self.minimum_needed_cubes(CubeColor::Green) <= 13
-            && self.minimum_needed_cubes(CubeColor::Red) <= 12
+    fn can_be_played(&self, bag: &CubeSet) -> bool {
+        bag.contains(&self.minimum_needed_cubes())
     }
 
-    fn calculate_power(&self) -> usize {
-        self.minimum_needed_cubes(CubeColor::Blue)
-            * self.minimum_needed_cubes(CubeColor::Green)
-            * self.minimum_needed_cubes(CubeColor::Red)
+    fn calculate_power(&self, colors: &[&str]) -> usize {
+        let needed = self.minimum_needed_cubes();
+        colors.iter().map(|color| needed.count(color)).product()
+    }
+}
+
+fn parse_games(input: &PuzzleInput, colors: &[&str]) -> Result<Vec<Game>, ParseError> {
+    input
+        .lines()
+        .map(|line| Game::parse(&line, colors))
+        .collect()
+}
+
+/// The smallest bag with which every game could have been played.
+fn minimal_bag(games: &[Game]) -> CubeSet {
+    games.iter().fold(CubeSet::default(), |bag, game| {
+        bag.max(&game.minimum_needed_cubes())
+    })
+}
+
+fn feasible_games<'a>(games: &'a [Game], bag: &CubeSet) -> Vec<&'a Game> {
+    games
+        .iter()
+        .filter(|game| game.can_be_played(bag))
+        .collect()
+}
+
+/// Finds a bag with at most `budget` cubes in total which allows playing as many games as
+/// possible. Returns the bag and the ids of the games that can be played with it.
+///
+/// The optimal count for each color is always one of the counts some game needs, so all
+/// combinations of those are tried for all colors but the last one, which gets the rest of the budget.
+fn largest_feasible_subset(
+    games: &[Game],
+    colors: &[&str],
+    budget: usize,
+) -> (CubeSet, Vec<usize>) {
+    let needed = games
+        .iter()
+        .map(|game| game.minimum_needed_cubes())
+        .collect::<Vec<_>>();
+    let candidates = needed.iter().collect::<Vec<_>>();
+    let (bag, _) = search_bag(&candidates, colors, budget, CubeSet::default());
+
+    let ids = games
+        .iter()
+        .zip(needed.iter())
+        .filter(|(_, needed)| bag.contains(needed))
+        .map(|(game, _)| game.id)
+        .collect();
+    (bag, ids)
+}
+
+/// Returns the best bag for the remaining colors and the number of candidates it can play.
+fn search_bag(
+    candidates: &[&CubeSet],
+    colors: &[&str],
+    budget: usize,
+    bag: CubeSet,
+) -> (CubeSet, usize) {
+    let Some((&color, remaining_colors)) = colors.split_first() else {
+        return (bag, candidates.len());
+    };
+
+    let mut counts = candidates
+        .iter()
+        .map(|needed| needed.count(color))
+        .filter(|&count| count <= budget)
+        .collect::<Vec<_>>();
+    counts.sort_unstable();
+    counts.dedup();
+    if remaining_colors.is_empty() {
+        // No other color needs budget, so only the largest count is worth checking.
+        counts.drain(..counts.len().saturating_sub(1));
+    }
+
+    let mut best = (bag.clone(), 0);
+    for count in counts {
+        let playable = candidates
+            .iter()
+            .copied()
+            .filter(|needed| needed.count(color) <= count)
+            .collect::<Vec<_>>();
+        if playable.len() <= best.1 {
+            continue;
+        }
+
+        let mut bag = bag.clone();
+        if count > 0 {
+            bag.counts.insert(color.to_string(), count);
+        }
+        let result = search_bag(&playable, remaining_colors, budget - count, bag);
+        if result.1 > best.1 {
+            best = result;
+        }
+    }
+
+    best
+}
+
+struct Options {
+    colors: Vec<String>,
+    bag: String,
+    budget: Option<usize>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            colors: DEFAULT_COLORS.iter().map(|c| c.to_string()).collect(),
+            bag: DEFAULT_BAG.to_string(),
+            budget: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+            match arg.as_str() {
+                "--colors" => {
+                    options.colors = value()?.split(',').map(|c| c.trim().to_string()).collect()
+                }
+                "--bag" => options.bag = value()?,
+                "--budget" => {
+                    let budget = value()?;
+                    let parsed = budget
+                        .parse()
+                        .map_err(|_| format!("Invalid budget: {}", budget))?;
+                    options.budget = Some(parsed);
+                }
+                _ => return Err(format!("Unknown argument: {}\n{}", arg, USAGE)),
+            }
+        }
+
+        Ok(options)
     }
 }
 
 fn main() {
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let colors = options
+        .colors
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let bag = CubeSet::parse(&options.bag, &colors).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    let games = parse_games(&input, &colors).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let feasible = feasible_games(&games, &bag)
+        .iter()
+        .map(|game| game.id)
+        .collect::<Vec<_>>();
+
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
+    println!(
+        "Games playable with {}: {:?} (sum of ids: {})",
+        bag,
+        feasible,
+        feasible.iter().sum::<usize>()
+    );
+    println!("Minimal bag for all games: {}", minimal_bag(&games));
+
+    if let Some(budget) = options.budget {
+        let (bag, ids) = largest_feasible_subset(&games, &colors, budget);
+        println!(
+            "Best bag with at most {} cubes: {} ({} cubes, {} games: {:?})",
+            budget,
+            bag,
+            bag.total(),
+            ids.len(),
+            ids
+        );
+    }
 }
 
 fn solve_a(input: &PuzzleInput) -> usize {
-    input
-        .lines()
-        .map(|line| Game::parse(&line))
-        .filter(|game| game.can_be_played())
+    let bag = CubeSet::parse(DEFAULT_BAG, &DEFAULT_COLORS).unwrap();
+    let games = parse_games(input, &DEFAULT_COLORS).expect("Invalid game");
+    feasible_games(&games, &bag)
+        .iter()
         .map(|game| game.id)
         .sum()
 }
 
 fn solve_b(input: &PuzzleInput) -> usize {
-    input
-        .lines()
-        .map(|line| Game::parse(&line))
-        .map(|game| game.calculate_power())
+    parse_games(input, &DEFAULT_COLORS)
+        .expect("Invalid game")
+        .iter()
+        .map(|game| game.calculate_power(&DEFAULT_COLORS))
         .sum()
 }
 
@@ -118,6 +344,102 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
         solve_b(&input);
     }
 
+    #[test]
+    fn test_parse_counts() {
+        let game = Game::parse(
+            "Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue",
+            &DEFAULT_COLORS,
+        )
+        .unwrap();
+        assert_eq!(game.id, 7);
+        assert_eq!(game.cube_sets[1].count("blue"), 6);
+        assert_eq!(game.cube_sets[1].count("red"), 1);
+
+        let needed = game.minimum_needed_cubes();
+        assert_eq!(
+            (
+                needed.count("red"),
+                needed.count("green"),
+                needed.count("blue")
+            ),
+            (4, 2, 6)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Game::parse("Game 1: 3 purple", &DEFAULT_COLORS).err(),
+            Some(ParseError::UnknownColor("purple".to_string()))
+        );
+        assert!(matches!(
+            Game::parse("Game 1: x red", &DEFAULT_COLORS),
+            Err(ParseError::InvalidCube(_))
+        ));
+        assert!(matches!(
+            Game::parse("Round 1: 1 red", &DEFAULT_COLORS),
+            Err(ParseError::InvalidGame(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_colors() {
+        let colors = ["purple", "orange"];
+        let game = Game::parse("Game 1: 3 purple; 2 orange, 1 purple", &colors).unwrap();
+        assert_eq!(game.calculate_power(&colors), 6);
+        assert!(Game::parse("Game 1: 3 red", &colors).is_err());
+        assert_eq!(
+            parse_games(&PuzzleInput::new(TEST_INPUT), &colors).err(),
+            Some(ParseError::UnknownColor("blue".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_minimal_bag() {
+        let games = parse_games(&PuzzleInput::new(TEST_INPUT), &DEFAULT_COLORS).unwrap();
+        let bag = minimal_bag(&games);
+        assert_eq!(bag.to_string(), "15 blue, 13 green, 20 red");
+        assert_eq!(feasible_games(&games, &bag).len(), games.len());
+    }
+
+    #[test]
+    fn test_feasible_games() {
+        let games = parse_games(&PuzzleInput::new(TEST_INPUT), &DEFAULT_COLORS).unwrap();
+        let bag = CubeSet::parse("6 red, 3 green, 6 blue", &DEFAULT_COLORS).unwrap();
+        let ids = feasible_games(&games, &bag)
+            .iter()
+            .map(|game| game.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn test_largest_feasible_subset() {
+        let games = parse_games(&PuzzleInput::new(TEST_INPUT), &DEFAULT_COLORS).unwrap();
+
+        // Game 1, 2 and 5 need at most 6 red, 3 green and 6 blue
+        let (bag, ids) = largest_feasible_subset(&games, &DEFAULT_COLORS, 15);
+        assert_eq!(ids, vec![1, 2, 5]);
+        assert!(bag.total() <= 15);
+
+        let (bag, ids) = largest_feasible_subset(&games, &DEFAULT_COLORS, 1000);
+        assert_eq!(ids.len(), 5);
+        assert_eq!(bag, minimal_bag(&games));
+
+        let (_, ids) = largest_feasible_subset(&games, &DEFAULT_COLORS, 2);
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_options() {
+        let args = ["--bag", "1 red", "--budget", "20"].map(String::from);
+        let options = Options::parse(args.into_iter()).unwrap();
+        assert_eq!(options.bag, "1 red");
+        assert_eq!(options.budget, Some(20));
+        assert!(Options::parse(["--budget"].map(String::from).into_iter()).is_err());
+        assert!(Options::parse(["--foo"].map(String::from).into_iter()).is_err());
+    }
+
     #[test]
     fn test_solve_a() {
         assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 8);
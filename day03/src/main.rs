@@ -1,4 +1,5 @@
-use aoc_utils::PuzzleInput;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 3;
 
 struct Number {
@@ -121,8 +122,8 @@ impl Map {
 
 fn main() {
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
 }
 
 fn solve_a(input: &PuzzleInput) -> usize {
@@ -1,11 +1,13 @@
-use aoc_utils::PuzzleInput;
+use aoc_utils::bitset::BitSet128;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 4;
 
 #[derive(Clone)]
 struct Card {
     id: usize,
-    winning_numbers: Vec<usize>,
-    numbers: Vec<usize>,
+    winning_numbers: BitSet128,
+    numbers: BitSet128,
 }
 
 impl Card {
@@ -16,7 +18,7 @@ impl Card {
             .next()
             .unwrap()
             .split(' ')
-            .last()
+            .next_back()
             .unwrap()
             .parse()
             .unwrap();
@@ -29,13 +31,13 @@ impl Card {
             .split(' ')
             .filter(|s| !s.is_empty())
             .map(|s| s.parse().unwrap())
-            .collect::<Vec<usize>>();
+            .collect::<BitSet128>();
 
         let numbers = numbers_str
             .split(' ')
             .filter(|s| !s.is_empty())
             .map(|s| s.parse().unwrap())
-            .collect::<Vec<usize>>();
+            .collect::<BitSet128>();
 
         Self {
             id,
@@ -45,10 +47,7 @@ impl Card {
     }
 
     fn count_matching_numbers(&self) -> usize {
-        self.numbers
-            .iter()
-            .filter(|number| self.winning_numbers.contains(number))
-            .count()
+        self.numbers.intersection(&self.winning_numbers).len()
     }
 
     fn calculate_points(&self) -> usize {
@@ -64,8 +63,8 @@ impl Card {
 
 fn main() {
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
 }
 
 fn solve_a(input: &PuzzleInput) -> usize {
@@ -111,7 +110,11 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
     fn parses_correctly() {
         let card = Card::parse("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53".to_string());
         assert_eq!(card.id, 1);
-        assert_eq!(card.winning_numbers, vec![41, 48, 83, 86, 17]);
+        assert_eq!(
+            card.winning_numbers,
+            [41, 48, 83, 86, 17].into_iter().collect::<BitSet128>()
+        );
+        assert_eq!(card.count_matching_numbers(), 4);
     }
 
     #[test]
@@ -1,14 +1,15 @@
 use std::ops::RangeInclusive;
 
-use aoc_utils::PuzzleInput;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 5;
 const START_CATEGORY: &str = "seed";
 const END_CATEGORY: &str = "location";
 
 fn main() {
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
 }
 
 fn range_split_at(
@@ -1,69 +1,220 @@
-use aoc_utils::PuzzleInput;
+use std::fmt;
+use std::ops::{Div, Sub};
+
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
+use num_bigint::BigUint;
+use num_integer::Roots;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, One, Zero};
 const DAY: u8 = 6;
 
-struct Race {
-    time: usize,
-    record_distance: usize,
+/// Unsigned integer types the race parameters can be stored in.
+/// Implemented for the primitive types and for `BigUint` if the numbers don't fit into 64 bits.
+trait RaceNumber:
+    Clone
+    + Ord
+    + fmt::Display
+    + Zero
+    + One
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + Sub<Output = Self>
+    + Div<Output = Self>
+    + Roots
+{
+}
+
+impl<T> RaceNumber for T where
+    T: Clone
+        + Ord
+        + fmt::Display
+        + Zero
+        + One
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + Sub<Output = Self>
+        + Div<Output = Self>
+        + Roots
+{
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RaceError {
+    MissingLine(&'static str),
+    InvalidNumber(String),
+    /// The time and distance lines have a different number of values.
+    CountMismatch {
+        times: usize,
+        distances: usize,
+    },
+    /// A number or an intermediate result doesn't fit into the chosen integer type.
+    Overflow(String),
+}
+
+impl fmt::Display for RaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLine(name) => write!(f, "Missing {} line", name),
+            Self::InvalidNumber(s) => write!(f, "Invalid number: {}", s),
+            Self::CountMismatch { times, distances } => write!(
+                f,
+                "Found {} times but {} distances, every race needs both",
+                times, distances
+            ),
+            Self::Overflow(s) => write!(f, "Overflow while {}", s),
+        }
+    }
+}
+
+struct Race<T> {
+    time: T,
+    record_distance: T,
 }
 
-impl Race {
-    fn new(time: usize, record_distance: usize) -> Self {
+impl<T: RaceNumber> Race<T> {
+    fn new(time: T, record_distance: T) -> Self {
         Self {
             time,
             record_distance,
         }
     }
 
-    fn does_beat_record(&self, hold_time: usize) -> bool {
-        let speed = hold_time;
-        let distance = speed * (self.time - hold_time);
-        distance > self.record_distance
+    fn does_beat_record(&self, hold_time: &T) -> bool {
+        let speed = hold_time.clone();
+        match speed.checked_mul(&(self.time.clone() - hold_time.clone())) {
+            Some(distance) => distance > self.record_distance,
+            // The record fits into T, so a distance that doesn't is always farther
+            None => true,
+        }
     }
 
-    fn determine_record_beating_hold_times(&self) -> Vec<usize> {
-        (0..=self.time)
-            .filter(|hold_time| self.does_beat_record(*hold_time))
-            .collect()
+    /// Counts the hold times `h` with `h * (time - h) > record_distance`.
+    ///
+    /// The winning hold times are the integers strictly between the roots of
+    /// `h^2 - time * h + record_distance`, which are symmetric around `time / 2`.
+    /// The lower root is estimated with an integer square root and then corrected,
+    /// so no floating point rounding is involved.
+    fn count_record_beating_hold_times(&self) -> Result<T, RaceError> {
+        let overflow = || RaceError::Overflow(format!("solving race with time {}", self.time));
+        let one = T::one();
+        let two = one.checked_add(&one).ok_or_else(overflow)?;
+        let four = two.checked_add(&two).ok_or_else(overflow)?;
+
+        let time_squared = self.time.checked_mul(&self.time).ok_or_else(overflow)?;
+        let four_distance = self
+            .record_distance
+            .checked_mul(&four)
+            .ok_or_else(overflow)?;
+        let discriminant = time_squared
+            .checked_sub(&four_distance)
+            .unwrap_or_else(T::zero);
+        let mut lowest = (self.time.clone() - discriminant.sqrt()) / two.clone();
+
+        while !lowest.is_zero() && self.does_beat_record(&(lowest.clone() - one.clone())) {
+            lowest = lowest - one.clone();
+        }
+        let half_time = self.time.clone() / two.clone();
+        while lowest <= half_time && !self.does_beat_record(&lowest) {
+            lowest = lowest.checked_add(&one).ok_or_else(overflow)?;
+        }
+
+        if lowest > half_time {
+            Ok(T::zero())
+        } else {
+            let upper = self.time.clone() - lowest.clone();
+            (upper - lowest).checked_add(&one).ok_or_else(overflow)
+        }
     }
 }
 
-fn parse_races(input: &PuzzleInput) -> Vec<Race> {
-    let time_line = input.lines().next().unwrap();
-    let distance_line = input.lines().nth(1).unwrap();
+/// Parses a decimal number, reporting numbers that are too large for `T` instead of wrapping.
+fn parse_number<T: RaceNumber>(s: &str) -> Result<T, RaceError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(RaceError::InvalidNumber(s.to_string()));
+    }
+    // Only digits are left, so the number can only be too large
+    T::from_str_radix(s, 10).map_err(|_| RaceError::Overflow(format!("parsing {}", s)))
+}
 
-    let times = time_line.split_whitespace().flat_map(|s| s.parse());
-    let distances = distance_line.split_whitespace().flat_map(|s| s.parse());
+/// Returns the numbers of the time and distance line, each without the `Time:` or `Distance:` label.
+fn number_lines(input: &PuzzleInput) -> Result<(String, String), RaceError> {
+    let mut lines = input.lines();
+    let time_line = lines.next().ok_or(RaceError::MissingLine("time"))?;
+    let distance_line = lines.next().ok_or(RaceError::MissingLine("distance"))?;
+
+    let strip_label = |line: String| match line.split_once(':') {
+        Some((_, numbers)) => numbers.to_string(),
+        None => line,
+    };
+    Ok((strip_label(time_line), strip_label(distance_line)))
+}
+
+fn parse_races<T: RaceNumber>(input: &PuzzleInput) -> Result<Vec<Race<T>>, RaceError> {
+    let (time_line, distance_line) = number_lines(input)?;
+    let times = time_line.split_whitespace().collect::<Vec<_>>();
+    let distances = distance_line.split_whitespace().collect::<Vec<_>>();
+    if times.len() != distances.len() {
+        return Err(RaceError::CountMismatch {
+            times: times.len(),
+            distances: distances.len(),
+        });
+    }
 
     times
+        .into_iter()
         .zip(distances)
-        .map(|(time, distance)| Race::new(time, distance))
+        .map(|(time, distance)| Ok(Race::new(parse_number(time)?, parse_number(distance)?)))
         .collect()
 }
 
+/// Parses the lines as a single race, ignoring the spaces between the numbers.
+fn parse_kerned_race<T: RaceNumber>(input: &PuzzleInput) -> Result<Race<T>, RaceError> {
+    let (time_line, distance_line) = number_lines(input)?;
+    let remove_spaces = |line: String| line.split_whitespace().collect::<String>();
+
+    Ok(Race::new(
+        parse_number(&remove_spaces(time_line))?,
+        parse_number(&remove_spaces(distance_line))?,
+    ))
+}
+
 fn main() {
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    let mut failed = false;
+    match solve_a(&input) {
+        Ok(a) => print_answer(DEFAULT_YEAR, DAY, Part::A, a),
+        Err(e) => {
+            eprintln!("A: {}", e);
+            failed = true;
+        }
+    }
+    match solve_b(&input) {
+        Ok(b) => print_answer(DEFAULT_YEAR, DAY, Part::B, b),
+        Err(e) => {
+            eprintln!("B: {}", e);
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
 }
 
-fn solve_a(input: &PuzzleInput) -> usize {
-    let races = parse_races(input);
+fn solve_a(input: &PuzzleInput) -> Result<u64, RaceError> {
+    let races = parse_races::<u64>(input)?;
 
-    races
-        .iter()
-        .map(|g| g.determine_record_beating_hold_times().len())
-        .product()
+    races.iter().try_fold(1, |product: u64, race| {
+        let count = race.count_record_beating_hold_times()?;
+        product
+            .checked_mul(count)
+            .ok_or_else(|| RaceError::Overflow("multiplying the race results".to_string()))
+    })
 }
 
-fn solve_b(input: &PuzzleInput) -> usize {
-    let races = parse_races(input);
-    let big_race = races.iter().fold(Race::new(0, 0), |a, b| {
-        let time_str = a.time.to_string() + &b.time.to_string();
-        let distance_str = a.record_distance.to_string() + &b.record_distance.to_string();
-        Race::new(time_str.parse().unwrap(), distance_str.parse().unwrap())
-    });
-
-    big_race.determine_record_beating_hold_times().len()
+fn solve_b(input: &PuzzleInput) -> Result<BigUint, RaceError> {
+    parse_kerned_race::<BigUint>(input)?.count_record_beating_hold_times()
 }
 
 #[cfg(test)]
@@ -73,20 +224,118 @@ mod tests {
     const TEST_INPUT: &str = "Time:      7  15   30
 Distance:  9  40  200";
 
+    fn count_by_brute_force(race: &Race<u64>) -> u64 {
+        (0..=race.time)
+            .filter(|hold_time| race.does_beat_record(hold_time))
+            .count() as u64
+    }
+
     #[test]
     fn test_no_panic() {
         let input = PuzzleInput::get_input(DAY);
-        solve_a(&input);
-        solve_b(&input);
+        solve_a(&input).unwrap();
+        solve_b(&input).unwrap();
+    }
+
+    #[test]
+    fn test_closed_form_matches_brute_force() {
+        for time in 0..60 {
+            for record_distance in 0..=(time * time / 4 + 1) {
+                let race = Race::new(time, record_distance);
+                assert_eq!(
+                    race.count_record_beating_hold_times(),
+                    Ok(count_by_brute_force(&race)),
+                    "time {} distance {}",
+                    time,
+                    record_distance
+                );
+
+                let big_race = Race::new(BigUint::from(time), BigUint::from(record_distance));
+                assert_eq!(
+                    big_race.count_record_beating_hold_times(),
+                    Ok(BigUint::from(count_by_brute_force(&race)))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number::<u8>("255"), Ok(255));
+        assert_eq!(
+            parse_number::<u8>("256"),
+            Err(RaceError::Overflow("parsing 256".to_string()))
+        );
+        assert_eq!(
+            parse_number::<u64>("12a"),
+            Err(RaceError::InvalidNumber("12a".to_string()))
+        );
+        // from_str_radix would accept the sign
+        assert_eq!(
+            parse_number::<u64>("+12"),
+            Err(RaceError::InvalidNumber("+12".to_string()))
+        );
+        assert_eq!(
+            parse_number::<BigUint>("123456789012345678901234567890"),
+            Ok("123456789012345678901234567890".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_count_mismatch() {
+        let input = PuzzleInput::new("Time:      7  15   30\nDistance:  9  40");
+        assert_eq!(
+            parse_races::<u64>(&input).err(),
+            Some(RaceError::CountMismatch {
+                times: 3,
+                distances: 2
+            })
+        );
+        assert!(solve_a(&input).is_err());
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        // 200 * 200 doesn't fit into an u8
+        let race = Race::<u8>::new(200, 100);
+        assert!(matches!(
+            race.count_record_beating_hold_times(),
+            Err(RaceError::Overflow(_))
+        ));
+
+        let input = PuzzleInput::new(
+            "Time:      71530 71530 71530 71530\nDistance:  940200 940200 940200 940200",
+        );
+        assert!(matches!(
+            parse_kerned_race::<u64>(&input),
+            Err(RaceError::Overflow(_))
+        ));
+        assert!(parse_kerned_race::<BigUint>(&input).is_ok());
+    }
+
+    #[test]
+    fn test_large_race() {
+        // Both roots of h^2 - 10^30 h + 10^58 are far beyond the range of u64
+        let time = BigUint::from(10u32).pow(30);
+        let record_distance = BigUint::from(10u32).pow(58);
+        let race = Race::new(time.clone(), record_distance.clone());
+        let count = race.count_record_beating_hold_times().unwrap();
+
+        let lowest = (time.clone() + BigUint::one() - count.clone()) / BigUint::from(2u32);
+        assert!(race.does_beat_record(&lowest));
+        assert!(!race.does_beat_record(&(lowest - BigUint::one())));
     }
 
     #[test]
     fn test_solve_a() {
-        assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 288);
+        assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), Ok(288));
     }
 
     #[test]
     fn test_solve_b() {
-        assert_eq!(solve_b(&PuzzleInput::new(TEST_INPUT)), 71503);
+        assert_eq!(
+            solve_b(&PuzzleInput::new(TEST_INPUT)),
+            Ok(BigUint::from(71503u32))
+        );
     }
 }
@@ -1,12 +1,102 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::cmp::Ordering;
 
-use aoc_utils::PuzzleInput;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 7;
+const USAGE: &str = "Usage: day07 [--ranking <weakest to strongest cards>] [--wildcards <cards>]
+             [--tie-break dealt|grouped] [--report table|csv]";
 
 fn main() {
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    });
+
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    if let Some(format) = options.report {
+        let mut rules = vec![
+            ("A".to_string(), Rules::standard()),
+            ("B".to_string(), Rules::jokers()),
+        ];
+        if let Some(custom) = options.rules {
+            rules.push(("Custom".to_string(), custom));
+        }
+        print!("{}", create_report(&input, &rules, format));
+        return;
+    }
+
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
+    if let Some(rules) = options.rules {
+        println!("Custom rules: {}", calculate_score(&input, &rules));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum ReportFormat {
+    Table,
+    Csv,
+}
+
+struct Options {
+    /// Custom rules, `None` if no rules were given.
+    rules: Option<Rules>,
+    report: Option<ReportFormat>,
+}
+
+impl Options {
+    /// Parses the command line. Custom rules start from the rules of part A.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut ranking = "23456789TJQKA".to_string();
+        let mut wildcards = String::new();
+        let mut tie_break = TieBreak::DealtOrder;
+        let mut rules_given = false;
+        let mut report = None;
+
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            match arg.as_str() {
+                "--ranking" => ranking = value,
+                "--wildcards" => wildcards = value,
+                "--tie-break" => {
+                    tie_break = match value.as_str() {
+                        "dealt" => TieBreak::DealtOrder,
+                        "grouped" => TieBreak::Grouped,
+                        _ => return Err(format!("Unknown tie break: {}", value)),
+                    }
+                }
+                "--report" => {
+                    report = Some(match value.as_str() {
+                        "table" => ReportFormat::Table,
+                        "csv" => ReportFormat::Csv,
+                        _ => return Err(format!("Unknown report format: {}", value)),
+                    });
+                    continue;
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+            rules_given = true;
+        }
+
+        let valid_cards = |cards: &str| cards.chars().all(|c| "23456789TJQKA".contains(c));
+        if ranking.len() != 13 || !valid_cards(&ranking) || !valid_cards(&wildcards) {
+            return Err(format!("Invalid cards in {} or {}", ranking, wildcards));
+        }
+        if !Card::all_cards()
+            .iter()
+            .all(|card| ranking.chars().filter(|c| Card::new(*c) == *card).count() == 1)
+        {
+            return Err("Ranking has to contain every card exactly once".to_string());
+        }
+
+        Ok(Self {
+            rules: rules_given.then(|| Rules::new(&ranking, &wildcards, tie_break)),
+            report,
+        })
+    }
 }
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
@@ -46,6 +136,10 @@ impl Card {
         }
     }
 
+    fn to_char(self) -> char {
+        "23456789TJQKA".chars().nth(self as usize).unwrap()
+    }
+
     fn all_cards() -> Vec<Self> {
         vec![
             Self::Two,
@@ -63,22 +157,77 @@ impl Card {
             Self::A,
         ]
     }
+}
 
-    fn card_to_num(&self, jokers_enabled: bool) -> i8 {
-        if jokers_enabled && *self == Self::J {
-            -1
-        } else {
-            *self as i8
+/// How hands of the same type are ordered.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum TieBreak {
+    /// Compare the cards in the order they were dealt, as in Camel Cards.
+    DealtOrder,
+    /// Compare the groups of equal cards, larger groups first and then by rank, as in poker.
+    Grouped,
+}
+
+/// Describes a variant of the game: how strong each card is, which cards act as wildcards
+/// when determining the hand type and how ties between hands of the same type are broken.
+#[derive(Debug, Clone)]
+struct Rules {
+    /// Strength of each card, indexed by `Card as usize`.
+    ranks: [u8; 13],
+    wildcards: [bool; 13],
+    tie_break: TieBreak,
+}
+
+impl Rules {
+    /// Creates rules from the cards ordered from weakest to strongest and the wildcards, both
+    /// given as card characters like `"23456789TJQKA"`.
+    fn new(ranking: &str, wildcards: &str, tie_break: TieBreak) -> Self {
+        let ranking: Vec<_> = ranking.chars().map(Card::new).collect();
+        assert!(
+            ranking.len() == 13 && Card::all_cards().iter().all(|c| ranking.contains(c)),
+            "Ranking has to contain every card exactly once"
+        );
+
+        let mut ranks = [0; 13];
+        for (rank, card) in ranking.iter().enumerate() {
+            ranks[*card as usize] = rank as u8;
+        }
+        let mut wild = [false; 13];
+        for card in wildcards.chars().map(Card::new) {
+            wild[card as usize] = true;
+        }
+
+        Self {
+            ranks,
+            wildcards: wild,
+            tie_break,
         }
     }
 
-    fn cmp_card(&self, other: &Self, jokers_enabled: bool) -> Ordering {
-        self.card_to_num(jokers_enabled)
-            .cmp(&other.card_to_num(jokers_enabled))
+    /// The rules of part A.
+    fn standard() -> Self {
+        Self::new("23456789TJQKA", "", TieBreak::DealtOrder)
+    }
+
+    /// The rules of part B, where jokers are wild but the weakest card.
+    fn jokers() -> Self {
+        Self::new("J23456789TQKA", "J", TieBreak::DealtOrder)
+    }
+
+    fn rank(&self, card: Card) -> u8 {
+        self.ranks[card as usize]
+    }
+
+    fn is_wild(&self, card: Card) -> bool {
+        self.wildcards[card as usize]
+    }
+
+    fn cmp_card(&self, a: Card, b: Card) -> Ordering {
+        self.rank(a).cmp(&self.rank(b))
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 enum CardHandType {
     HighCard,
     OnePair,
@@ -89,119 +238,260 @@ enum CardHandType {
     FiveOfAKind,
 }
 
+impl CardHandType {
+    const ALL: [Self; 7] = [
+        Self::HighCard,
+        Self::OnePair,
+        Self::TwoPair,
+        Self::ThreeOfAKind,
+        Self::FullHouse,
+        Self::FourOfAKind,
+        Self::FiveOfAKind,
+    ];
+
+    /// Classifies a hand by the sizes of its groups of equal cards, sorted from largest to smallest.
+    /// Groups larger than five cards, possible in larger hands, count as five of a kind.
+    fn from_group_sizes(groups: &[u8]) -> Self {
+        match groups {
+            [largest, ..] if *largest >= 5 => Self::FiveOfAKind,
+            [4, ..] => Self::FourOfAKind,
+            [3, second, ..] if *second >= 2 => Self::FullHouse,
+            [3, ..] => Self::ThreeOfAKind,
+            [2, 2, ..] => Self::TwoPair,
+            [2, ..] => Self::OnePair,
+            _ => Self::HighCard,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct CardHand {
-    cards: [Card; 5],
-    bid: u16,
+    cards: Vec<Card>,
+    bid: usize,
 }
 
 impl CardHand {
+    /// The most cards a hand can have, so that its sort key still fits into an `u128`.
+    const MAX_SIZE: usize = 31;
+
     fn parse(line: &str) -> Self {
         let mut split = line.split_whitespace();
         let cards: Vec<_> = split.next().unwrap().chars().map(Card::new).collect();
-        let bid = split.next().unwrap().parse::<u16>().unwrap();
-
-        Self {
-            cards: [cards[0], cards[1], cards[2], cards[3], cards[4]],
-            bid,
-        }
+        let bid = split.next().unwrap().parse::<usize>().unwrap();
+        assert!(
+            cards.len() <= Self::MAX_SIZE,
+            "Hands can have at most {} cards",
+            Self::MAX_SIZE
+        );
+
+        Self { cards, bid }
     }
 
-    fn get_type(&self, jokers_enabled: bool) -> CardHandType {
-        let all_possible_cards = Card::all_cards();
-        let mut group_by_count = HashMap::new();
-
+    /// Determines the type from the sizes of the groups of equal cards. Wildcards always
+    /// make the best hand by joining the largest group.
+    fn get_type(&self, rules: &Rules) -> CardHandType {
+        let mut counts = [0u8; 13];
+        let mut wildcards = 0;
         for card in self.cards.iter() {
-            if jokers_enabled && *card == Card::J {
-                for card in all_possible_cards.iter() {
-                    let count = group_by_count.entry(card).or_insert(0);
-                    *count += 1;
-                }
+            if rules.is_wild(*card) {
+                wildcards += 1;
             } else {
-                let count = group_by_count.entry(card).or_insert(0);
-                *count += 1;
+                counts[*card as usize] += 1;
             }
         }
 
-        // Five of a kind
-        if group_by_count.values().any(|&v| v >= 5) {
-            return CardHandType::FiveOfAKind;
-        }
-
-        // Four of a kind
-        if group_by_count.values().any(|&v| v == 4) {
-            return CardHandType::FourOfAKind;
-        }
-
-        // Full house
-        if group_by_count.values().any(|&v| v >= 3) && group_by_count.values().any(|&v| v >= 2) {
-            return CardHandType::FullHouse;
+        let mut groups: Vec<u8> = counts.into_iter().filter(|&count| count > 0).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        match groups.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => groups.push(wildcards),
         }
 
-        // Three of a kind
-        if group_by_count.values().any(|&v| v >= 3) {
-            return CardHandType::ThreeOfAKind;
-        }
-
-        // Two pair
-        if group_by_count.values().filter(|&&v| v >= 2).count() == 2 {
-            return CardHandType::TwoPair;
-        }
-
-        // One pair
-        if group_by_count.values().any(|&v| v >= 2) {
-            return CardHandType::OnePair;
-        }
-
-        // High card
-        CardHandType::HighCard
+        CardHandType::from_group_sizes(&groups)
     }
 
-    fn cmp_hand(&self, other: &Self, jokers_enabled: bool) -> Ordering {
-        let self_type = self.get_type(jokers_enabled);
-        let other_type = other.get_type(jokers_enabled);
+    /// The cards ordered by the size of their group and then by rank, strongest first.
+    fn grouped_cards(&self, rules: &Rules) -> Vec<Card> {
+        let mut cards = self.cards.to_vec();
+        let count = |card: &Card| self.cards.iter().filter(|c| *c == card).count();
+        cards.sort_by(|a, b| count(b).cmp(&count(a)).then_with(|| rules.cmp_card(*b, *a)));
+        cards
+    }
 
-        if self_type == other_type {
-            for i in 0..self.cards.len() {
-                if self.cards[i] != other.cards[i] {
-                    return self.cards[i].cmp_card(&other.cards[i], jokers_enabled);
-                }
-            }
-            Ordering::Equal
-        } else {
-            self_type.cmp(&other_type)
+    /// Packs the hand type and the ranks of the cards in tie break order into one integer,
+    /// so that comparing the keys of two hands compares the hands.
+    ///
+    /// The type takes the top 3 bits, followed by 4 bits per card. Ranks are stored
+    /// off by one, so shorter hands are padded with zeros and lose against longer ones.
+    fn sort_key(&self, rules: &Rules) -> u128 {
+        let cards = match rules.tie_break {
+            TieBreak::DealtOrder => self.cards.clone(),
+            TieBreak::Grouped => self.grouped_cards(rules),
+        };
+
+        let mut key = self.get_type(rules) as u128;
+        for i in 0..Self::MAX_SIZE {
+            let rank = cards.get(i).map_or(0, |card| rules.rank(*card) as u128 + 1);
+            key = key << 4 | rank;
         }
+        key
     }
 }
 
-fn calculate_score(input: &PuzzleInput, jokers_enabled: bool) -> usize {
+fn calculate_score(input: &PuzzleInput, rules: &Rules) -> usize {
     let mut hands = input
         .lines()
-        .map(|line| CardHand::parse(&line))
+        .map(|line| {
+            let hand = CardHand::parse(&line);
+            (hand.sort_key(rules), hand.bid)
+        })
         .collect::<Vec<_>>();
 
-    hands.sort_by(|a, b| a.cmp_hand(b, jokers_enabled));
-    // hands
-    //     .iter()
-    //     .for_each(|hand| println!("{:?} {:?}", hand, hand.get_type(jokers_enabled)));
+    hands.sort_unstable_by_key(|(key, _)| *key);
 
     hands
         .iter()
         .enumerate()
-        .map(|(i, hand)| hand.bid as usize * (i + 1))
+        .map(|(i, (_, bid))| bid * (i + 1))
         .sum()
 }
 
+/// Ranks of the hands in input order, when they are ordered by the given rules.
+fn rank_hands(hands: &[CardHand], rules: &Rules) -> Vec<usize> {
+    let mut order = (0..hands.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|i| hands[*i].sort_key(rules));
+
+    let mut ranks = vec![0; hands.len()];
+    for (rank, i) in order.into_iter().enumerate() {
+        ranks[i] = rank + 1;
+    }
+    ranks
+}
+
+/// Lists every hand with its type, rank and winnings under each of the rules, followed by
+/// the total winnings and a histogram of the hand types.
+fn create_report(input: &PuzzleInput, rules: &[(String, Rules)], format: ReportFormat) -> String {
+    let hands = input
+        .lines()
+        .map(|line| CardHand::parse(&line))
+        .collect::<Vec<_>>();
+    let ranks = rules
+        .iter()
+        .map(|(_, rules)| rank_hands(&hands, rules))
+        .collect::<Vec<_>>();
+
+    let mut header = vec!["Hand".to_string(), "Bid".to_string()];
+    for (name, _) in rules.iter() {
+        header.push(format!("Type {}", name));
+        header.push(format!("Rank {}", name));
+        header.push(format!("Winnings {}", name));
+    }
+
+    let mut hand_rows = vec![header];
+    for (i, hand) in hands.iter().enumerate() {
+        let mut row = vec![
+            hand.cards.iter().map(|card| card.to_char()).collect(),
+            hand.bid.to_string(),
+        ];
+        for ((_, rules), ranks) in rules.iter().zip(ranks.iter()) {
+            row.push(format!("{:?}", hand.get_type(rules)));
+            row.push(ranks[i].to_string());
+            row.push((hand.bid * ranks[i]).to_string());
+        }
+        hand_rows.push(row);
+    }
+
+    let mut total_row = vec!["Total".to_string(), String::new()];
+    for ranks in ranks.iter() {
+        let total: usize = hands
+            .iter()
+            .zip(ranks)
+            .map(|(hand, rank)| hand.bid * rank)
+            .sum();
+        total_row.extend([String::new(), String::new(), total.to_string()]);
+    }
+    hand_rows.push(total_row);
+
+    let mut histogram_rows = vec![std::iter::once("Type".to_string())
+        .chain(rules.iter().map(|(name, _)| name.clone()))
+        .collect::<Vec<_>>()];
+    for hand_type in CardHandType::ALL.iter().rev() {
+        let mut row = vec![format!("{:?}", hand_type)];
+        for (_, rules) in rules.iter() {
+            let count = hands
+                .iter()
+                .filter(|hand| hand.get_type(rules) == *hand_type)
+                .count();
+            row.push(count.to_string());
+        }
+        histogram_rows.push(row);
+    }
+
+    let format_rows = match format {
+        ReportFormat::Table => format_table,
+        ReportFormat::Csv => format_csv,
+    };
+    format!(
+        "{}\n{}",
+        format_rows(&hand_rows),
+        format_rows(&histogram_rows)
+    )
+}
+
+/// Formats the rows as a table with a separator below the header. Numbers are right aligned.
+fn format_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|row| row.get(c))
+                .map(|cell| cell.len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut table = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| {
+                if !cell.is_empty() && cell.chars().all(|c| c.is_ascii_digit()) {
+                    format!("{:>width$}", cell, width = width)
+                } else {
+                    format!("{:<width$}", cell, width = width)
+                }
+            })
+            .collect::<Vec<_>>();
+        table += cells.join(" | ").trim_end();
+        table.push('\n');
+
+        if i == 0 {
+            let separator = widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+            table += &separator.join("-+-");
+            table.push('\n');
+        }
+    }
+    table
+}
+
+fn format_csv(rows: &[Vec<String>]) -> String {
+    rows.iter().map(|row| row.join(",") + "\n").collect()
+}
+
 fn solve_a(input: &PuzzleInput) -> usize {
-    calculate_score(input, false)
+    calculate_score(input, &Rules::standard())
 }
 
 fn solve_b(input: &PuzzleInput) -> usize {
-    calculate_score(input, true)
+    calculate_score(input, &Rules::jokers())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
 
     const TEST_INPUT: &str = "32T3K 765
@@ -217,6 +507,312 @@ QQQJA 483";
         solve_b(&input);
     }
 
+    #[test]
+    fn test_reversed_ranking() {
+        let rules = Rules::new("AKQJT98765432", "", TieBreak::DealtOrder);
+        let low = CardHand::parse("22345 1");
+        let high = CardHand::parse("AA345 1");
+        assert_eq!(cmp_hand(&low, &high, &rules), Ordering::Greater);
+        assert_eq!(cmp_hand(&low, &high, &Rules::standard()), Ordering::Less);
+    }
+
+    #[test]
+    fn test_multiple_wildcards() {
+        let rules = Rules::new("23456789TJQKA", "29", TieBreak::DealtOrder);
+        assert_eq!(
+            CardHand::parse("2K9KA 1").get_type(&rules),
+            CardHandType::FourOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("29999 1").get_type(&rules),
+            CardHandType::FiveOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("2K9KA 1").get_type(&Rules::standard()),
+            CardHandType::OnePair
+        );
+    }
+
+    #[test]
+    fn test_grouped_tie_break() {
+        let rules = Rules::new("23456789TJQKA", "", TieBreak::Grouped);
+        let threes_full = CardHand::parse("22333 1");
+        let twos_full = CardHand::parse("33222 1");
+        assert_eq!(
+            cmp_hand(&threes_full, &twos_full, &rules),
+            Ordering::Greater
+        );
+        assert_eq!(
+            cmp_hand(&threes_full, &twos_full, &Rules::standard()),
+            Ordering::Less
+        );
+
+        // Same pair, so the highest kicker decides
+        let ace_kicker = CardHand::parse("K55A2 1");
+        let king_kicker = CardHand::parse("5Q5K3 1");
+        assert_eq!(
+            cmp_hand(&ace_kicker, &king_kicker, &rules),
+            Ordering::Greater
+        );
+    }
+
+    /// Classifies a hand without wildcards by counting pairs and triples.
+    fn classify_naive(cards: &[Card; 5]) -> CardHandType {
+        let mut counts = [0; 13];
+        for card in cards {
+            counts[*card as usize] += 1;
+        }
+        let has = |n| counts.contains(&n);
+        let pairs = counts.iter().filter(|&&c| c == 2).count();
+
+        if has(5) {
+            CardHandType::FiveOfAKind
+        } else if has(4) {
+            CardHandType::FourOfAKind
+        } else if has(3) && has(2) {
+            CardHandType::FullHouse
+        } else if has(3) {
+            CardHandType::ThreeOfAKind
+        } else if pairs == 2 {
+            CardHandType::TwoPair
+        } else if pairs == 1 {
+            CardHandType::OnePair
+        } else {
+            CardHandType::HighCard
+        }
+    }
+
+    /// The best type reachable by replacing the wildcards with any other cards.
+    /// The type doesn't depend on the order of the cards, so the substitutes are only tried
+    /// in ascending order starting at `first_substitute`.
+    fn classify_by_substitution(
+        cards: [Card; 5],
+        rules: &Rules,
+        substitutes: &[Card],
+        first_substitute: usize,
+    ) -> CardHandType {
+        match cards.iter().position(|card| rules.is_wild(*card)) {
+            None => classify_naive(&cards),
+            Some(i) => (first_substitute..substitutes.len())
+                .map(|s| {
+                    let mut substituted = cards;
+                    substituted[i] = substitutes[s];
+                    classify_by_substitution(substituted, rules, substitutes, s)
+                })
+                .max()
+                // Only wildcards exist, so they can't pretend to be anything else
+                .unwrap_or_else(|| classify_naive(&cards)),
+        }
+    }
+
+    #[test]
+    fn test_get_type_matches_substitution() {
+        let all_cards = Card::all_cards();
+        let rules = [
+            Rules::standard(),
+            Rules::jokers(),
+            Rules::new("23456789TJQKA", "2QK", TieBreak::DealtOrder),
+        ];
+        let substitutes = rules
+            .iter()
+            .map(|rules| {
+                all_cards
+                    .iter()
+                    .copied()
+                    .filter(|card| !rules.is_wild(*card))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut oracle_caches = vec![HashMap::new(); rules.len()];
+
+        for i in 0..13usize.pow(5) {
+            let mut cards = [Card::Two; 5];
+            let mut rest = i;
+            for card in cards.iter_mut() {
+                *card = all_cards[rest % 13];
+                rest /= 13;
+            }
+            let hand = CardHand {
+                cards: cards.to_vec(),
+                bid: 0,
+            };
+
+            // The type only depends on which cards the hand contains, so the oracle
+            // is only evaluated once for all permutations of a hand.
+            let mut sorted = cards;
+            sorted.sort_by_key(|card| *card as usize);
+
+            for ((rules, substitutes), oracle_cache) in rules
+                .iter()
+                .zip(substitutes.iter())
+                .zip(oracle_caches.iter_mut())
+            {
+                let expected = *oracle_cache
+                    .entry(sorted)
+                    .or_insert_with(|| classify_by_substitution(sorted, rules, substitutes, 0));
+                assert_eq!(hand.get_type(rules), expected, "{:?}", cards);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_type_groups() {
+        let jokers = Rules::jokers();
+        // The joker must not count for itself or complete a full house on its own
+        assert_eq!(
+            CardHand::parse("JJ234 1").get_type(&jokers),
+            CardHandType::ThreeOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("J2234 1").get_type(&jokers),
+            CardHandType::ThreeOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("J2233 1").get_type(&jokers),
+            CardHandType::FullHouse
+        );
+        assert_eq!(
+            CardHand::parse("JJJJJ 1").get_type(&jokers),
+            CardHandType::FiveOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("JJ234 1").get_type(&Rules::standard()),
+            CardHandType::OnePair
+        );
+    }
+
+    fn cmp_hand(a: &CardHand, b: &CardHand, rules: &Rules) -> Ordering {
+        a.sort_key(rules).cmp(&b.sort_key(rules))
+    }
+
+    /// Compares the types and then the cards one by one, without packing them into a key.
+    fn cmp_by_cards(a: &CardHand, b: &CardHand, rules: &Rules) -> Ordering {
+        let (a_cards, b_cards) = match rules.tie_break {
+            TieBreak::DealtOrder => (a.cards.clone(), b.cards.clone()),
+            TieBreak::Grouped => (a.grouped_cards(rules), b.grouped_cards(rules)),
+        };
+
+        a.get_type(rules).cmp(&b.get_type(rules)).then_with(|| {
+            a_cards
+                .iter()
+                .zip(b_cards.iter())
+                .map(|(a, b)| rules.cmp_card(*a, *b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    #[test]
+    fn test_sort_key_matches_comparison() {
+        let all_rules = [
+            Rules::standard(),
+            Rules::jokers(),
+            Rules::new("AKQJT98765432", "29", TieBreak::Grouped),
+        ];
+        let all_cards = Card::all_cards();
+
+        for size in [1, 3, 5, 8, CardHand::MAX_SIZE] {
+            // Deterministic pseudo random hands with many repeated cards
+            let mut state = 12345u64;
+            let hands = (0..50)
+                .map(|_| {
+                    let cards = (0..size)
+                        .map(|_| {
+                            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                            all_cards[(state >> 33) as usize % 6]
+                        })
+                        .collect();
+                    CardHand { cards, bid: 0 }
+                })
+                .collect::<Vec<_>>();
+
+            for rules in all_rules.iter() {
+                for a in hands.iter() {
+                    for b in hands.iter() {
+                        assert_eq!(
+                            cmp_hand(a, b, rules),
+                            cmp_by_cards(a, b, rules),
+                            "{:?} {:?}",
+                            a.cards,
+                            b.cards
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_variable_hand_size_and_large_bids() {
+        let input = PuzzleInput::new("2345 100000\nAAAK 4000000000\n22A3 70000");
+        // Ranks: 2345 high card, 22A3 one pair, AAAK three of a kind
+        assert_eq!(
+            calculate_score(&input, &Rules::standard()),
+            100000 + 2 * 70000 + 3 * 4000000000
+        );
+        assert_eq!(
+            CardHand::parse("222 1").get_type(&Rules::standard()),
+            CardHandType::ThreeOfAKind
+        );
+        assert_eq!(
+            CardHand::parse("2222222 1").get_type(&Rules::standard()),
+            CardHandType::FiveOfAKind
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hand_too_large() {
+        CardHand::parse("23456789TJQKA23456789TJQKA23456789 1");
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let args = ["--wildcards", "J", "--tie-break", "grouped"].map(String::from);
+        let rules = Options::parse(args.into_iter()).unwrap().rules.unwrap();
+        assert!(rules.is_wild(Card::J));
+        assert_eq!(rules.tie_break, TieBreak::Grouped);
+
+        let options = Options::parse(std::iter::empty()).unwrap();
+        assert!(options.rules.is_none() && options.report.is_none());
+        let report = ["--report", "csv"].map(String::from);
+        let options = Options::parse(report.into_iter()).unwrap();
+        assert!(options.rules.is_none());
+        assert_eq!(options.report, Some(ReportFormat::Csv));
+
+        let duplicate = ["--ranking", "22456789TJQKA"].map(String::from);
+        assert!(Options::parse(duplicate.into_iter()).is_err());
+        let unknown = ["--tie-break", "random"].map(String::from);
+        assert!(Options::parse(unknown.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let rules = [
+            ("A".to_string(), Rules::standard()),
+            ("B".to_string(), Rules::jokers()),
+        ];
+        let csv = create_report(&PuzzleInput::new(TEST_INPUT), &rules, ReportFormat::Csv);
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "Hand,Bid,Type A,Rank A,Winnings A,Type B,Rank B,Winnings B"
+        );
+        assert_eq!(lines[2], "T55J5,684,ThreeOfAKind,4,2736,FourOfAKind,3,2052");
+        assert_eq!(lines[6], "Total,,,,6440,,,5905");
+        assert_eq!(lines[8], "Type,A,B");
+        assert_eq!(lines[9], "FiveOfAKind,0,0");
+        assert_eq!(lines[10], "FourOfAKind,0,3");
+        assert_eq!(lines[15], "HighCard,0,0");
+
+        let table = create_report(&PuzzleInput::new(TEST_INPUT), &rules, ReportFormat::Table);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("Hand  | Bid | Type A       | Rank A |"));
+        assert!(lines[1].starts_with("------+-----+--------------+--------+"));
+        assert!(lines[2].starts_with("32T3K | 765 | OnePair      |      1 |"));
+    }
+
     #[test]
     fn test_solve_a() {
         assert_eq!(solve_a(&PuzzleInput::new(TEST_INPUT)), 6440);
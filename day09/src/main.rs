@@ -1,10 +1,11 @@
-use aoc_utils::PuzzleInput;
+use aoc_utils::ledger::{print_answer, Part};
+use aoc_utils::{PuzzleInput, DEFAULT_YEAR};
 const DAY: u8 = 9;
 
 fn main() {
     let input = PuzzleInput::get_input(DAY);
-    println!("A: {}", solve_a(&input));
-    println!("B: {}", solve_b(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::A, solve_a(&input));
+    print_answer(DEFAULT_YEAR, DAY, Part::B, solve_b(&input));
 }
 
 fn parse_numbers(line: String) -> Vec<i64> {
@@ -13,57 +14,65 @@ fn parse_numbers(line: String) -> Vec<i64> {
         .collect()
 }
 
-fn calculate_diffs(history: Vec<i64>) -> Vec<Vec<i64>> {
-    let mut difference_vecs: Vec<Vec<i64>> = Vec::new();
-    difference_vecs.push(history.clone());
-
-    while !difference_vecs.last().unwrap().iter().all(|v| *v == 0) {
-        let mut difference_vec = Vec::new();
-        let previous_diffs = difference_vecs.last().unwrap();
-        for i in 0..previous_diffs.len() - 1 {
-            difference_vec.push(previous_diffs[i + 1] - previous_diffs[i]);
-        }
-        difference_vecs.push(difference_vec);
+/// Binomial coefficient `n choose k`, or `None` if it doesn't fit into an `i128`.
+fn binomial(n: usize, k: usize) -> Option<i128> {
+    if k > n {
+        return Some(0);
     }
+    let k = k.min(n - k);
 
-    difference_vecs
+    // After step j the value is (n - k + j) choose j, which is always an integer
+    (1..=k).try_fold(1i128, |c, j| {
+        Some(c.checked_mul((n - k + j) as i128)? / j as i128)
+    })
 }
 
-fn predict_next_value(history: Vec<i64>) -> i64 {
-    let mut difference_vecs: Vec<Vec<i64>> = calculate_diffs(history);
-
-    // Compute the next difference for each order
-    difference_vecs.last_mut().unwrap().push(0);
-    for i in (0..difference_vecs.len() - 1).rev() {
-        let new_value = {
-            let current_history = &difference_vecs[i];
-            let higher_history = &difference_vecs[i + 1];
-
-            current_history.last().unwrap() + higher_history.last().unwrap()
-        };
-
-        difference_vecs[i].push(new_value);
+/// Extrapolates the history `steps` positions past its last value, so `steps = 1` is the next value.
+///
+/// Continuing the difference pyramid is the same as evaluating the polynomial of degree
+/// `n - 1` through the `n` values of the history. With the values at positions `0..n`, the
+/// Lagrange basis polynomial of value `i` at position `t >= n` simplifies to
+/// `(-1)^(n - 1 - i) * C(t, i) * C(t - i - 1, n - 1 - i)`, so no differences have to be built.
+///
+/// Returns `None` for an empty history or if the result doesn't fit into an `i64`.
+fn extrapolate_forward(history: &[i64], steps: usize) -> Option<i64> {
+    let n = history.len();
+    if n == 0 {
+        return None;
+    }
+    if steps == 0 {
+        return history.last().copied();
     }
 
-    *difference_vecs.first().unwrap().last().unwrap()
+    let t = n - 1 + steps;
+    let value = history
+        .iter()
+        .enumerate()
+        .try_fold(0i128, |sum, (i, value)| {
+            let weight = binomial(t, i)?.checked_mul(binomial(t - i - 1, n - 1 - i)?)?;
+            let term = weight.checked_mul(*value as i128)?;
+            if (n - 1 - i).is_multiple_of(2) {
+                sum.checked_add(term)
+            } else {
+                sum.checked_sub(term)
+            }
+        })?;
+
+    i64::try_from(value).ok()
 }
 
-fn predict_previous_value(history: Vec<i64>) -> i64 {
-    let mut difference_vecs: Vec<Vec<i64>> = calculate_diffs(history);
-
-    difference_vecs.last_mut().unwrap().push(0);
-    for i in (0..difference_vecs.len() - 1).rev() {
-        let new_value = {
-            let current_history = &difference_vecs[i];
-            let higher_history = &difference_vecs[i + 1];
-
-            current_history.first().unwrap() - higher_history.first().unwrap()
-        };
+/// Extrapolates the history `steps` positions before its first value.
+fn extrapolate_backward(history: &[i64], steps: usize) -> Option<i64> {
+    let reversed = history.iter().rev().copied().collect::<Vec<_>>();
+    extrapolate_forward(&reversed, steps)
+}
 
-        difference_vecs[i].insert(0, new_value);
-    }
+fn predict_next_value(history: Vec<i64>) -> i64 {
+    extrapolate_forward(&history, 1).expect("Next value does not fit into an i64")
+}
 
-    *difference_vecs.first().unwrap().first().unwrap()
+fn predict_previous_value(history: Vec<i64>) -> i64 {
+    extrapolate_backward(&history, 1).expect("Previous value does not fit into an i64")
 }
 
 fn solve_a(input: &PuzzleInput) -> i64 {
@@ -84,12 +93,123 @@ fn solve_b(input: &PuzzleInput) -> i64 {
 
 #[cfg(test)]
 mod tests {
+    use aoc_utils::sequence::{difference_table, Polynomial, Rational};
+
     use super::*;
 
     const TEST_INPUT: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45";
 
+    /// Continues the difference pyramid by one value at the end.
+    fn pyramid_next_value(history: Vec<i64>) -> i64 {
+        let mut difference_vecs: Vec<Vec<i64>> = difference_table(&history).unwrap();
+
+        difference_vecs.last_mut().unwrap().push(0);
+        for i in (0..difference_vecs.len() - 1).rev() {
+            let new_value =
+                difference_vecs[i].last().unwrap() + difference_vecs[i + 1].last().unwrap();
+            difference_vecs[i].push(new_value);
+        }
+
+        *difference_vecs.first().unwrap().last().unwrap()
+    }
+
+    /// Continues the difference pyramid by one value at the start.
+    fn pyramid_previous_value(history: Vec<i64>) -> i64 {
+        let mut difference_vecs: Vec<Vec<i64>> = difference_table(&history).unwrap();
+
+        difference_vecs.last_mut().unwrap().push(0);
+        for i in (0..difference_vecs.len() - 1).rev() {
+            let new_value =
+                difference_vecs[i].first().unwrap() - difference_vecs[i + 1].first().unwrap();
+            difference_vecs[i].insert(0, new_value);
+        }
+
+        *difference_vecs.first().unwrap().first().unwrap()
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(0, 0), Some(1));
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(2, 5), Some(0));
+        assert_eq!(binomial(60, 30), Some(118264581564861424));
+        assert_eq!(binomial(1000, 500), None);
+    }
+
+    #[test]
+    fn test_matches_pyramid() {
+        // Deterministic pseudo random histories of different lengths
+        let mut state = 42u64;
+        let mut random = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((state >> 33) % 41) as i64 - 20
+        };
+
+        for len in 1..=12 {
+            for _ in 0..50 {
+                let history = (0..len).map(|_| random()).collect::<Vec<_>>();
+
+                let mut forward = history.clone();
+                let mut backward = history.clone();
+                for steps in 1..=5 {
+                    forward.push(pyramid_next_value(forward.clone()));
+                    backward.insert(0, pyramid_previous_value(backward.clone()));
+
+                    assert_eq!(
+                        extrapolate_forward(&history, steps),
+                        Some(*forward.last().unwrap()),
+                        "{:?} {}",
+                        history,
+                        steps
+                    );
+                    assert_eq!(
+                        extrapolate_backward(&history, steps),
+                        Some(backward[0]),
+                        "{:?} {}",
+                        history,
+                        steps
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_interpolation() {
+        for line in TEST_INPUT.lines() {
+            let history = parse_numbers(line.to_string());
+            let polynomial = Polynomial::from_sequence(&history);
+            for steps in 1..=10 {
+                let position = Rational::from((history.len() - 1 + steps) as i64);
+                assert_eq!(
+                    Rational::from(extrapolate_forward(&history, steps).unwrap()),
+                    polynomial.evaluate(position)
+                );
+                assert_eq!(
+                    Rational::from(extrapolate_backward(&history, steps).unwrap()),
+                    polynomial.evaluate(Rational::from(-(steps as i64)))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(extrapolate_forward(&[], 1), None);
+        assert_eq!(extrapolate_forward(&[i64::MAX - 1, i64::MAX], 1), None);
+        assert_eq!(extrapolate_backward(&[i64::MIN, i64::MIN + 1], 1), None);
+        assert_eq!(
+            extrapolate_forward(&[i64::MAX - 2, i64::MAX - 1], 1),
+            Some(i64::MAX)
+        );
+        assert_eq!(
+            extrapolate_forward(&[1, -1, 1, -1, 1, -1, 1, -1], 1_000_000),
+            None
+        );
+    }
+
     #[test]
     fn test_no_panic() {
         let input = PuzzleInput::get_input(DAY);